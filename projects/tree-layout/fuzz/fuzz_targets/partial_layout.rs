@@ -0,0 +1,78 @@
+#![no_main]
+
+use arbitrary::{Arbitrary, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use tree_layout::LayoutConfig;
+
+#[path = "../../tests/partial_layout_test/edits.rs"]
+mod edits;
+
+use edits::{apply, build_tree, full_layout, Edit};
+
+/// Sizes are at least one, like the sizes of the property test.
+fn size(u: &mut Unstructured) -> arbitrary::Result<u8> {
+    u.int_in_range(1..=255)
+}
+
+impl<'a> Arbitrary<'a> for Edit {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => Edit::Insert { parent: u.arbitrary::<u16>()? as usize, width: size(u)?, height: size(u)? },
+            1 => Edit::Resize { node: u.arbitrary::<u16>()? as usize, width: size(u)?, height: size(u)? },
+            2 => Edit::Remove { node: u.arbitrary::<u16>()? as usize },
+            _ => Edit::Reparent { node: u.arbitrary::<u16>()? as usize, parent: u.arbitrary::<u16>()? as usize },
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Input {
+    nodes: Vec<(usize, u8, u8)>,
+    edits: Vec<Edit>,
+}
+
+impl<'a> Arbitrary<'a> for Input {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut nodes = vec![];
+        for _ in 0..u.int_in_range(0..=256)? {
+            nodes.push((u.arbitrary::<u16>()? as usize, size(u)?, size(u)?));
+        }
+        let mut edits = vec![];
+        for _ in 0..u.int_in_range(0..=64)? {
+            edits.push(Edit::arbitrary(u)?);
+        }
+        Ok(Self { nodes, edits })
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let mut layout = LayoutConfig::new(10., 10.);
+    let mut root = build_tree(&input.nodes);
+    let mut next_id = input.nodes.len();
+    layout.layout(&mut root);
+    for edit in input.edits.iter() {
+        let changed = match apply(&mut root, edit, &mut next_id) {
+            Some(s) => s,
+            None => continue,
+        };
+        layout.try_partial_layout(&mut root, &changed).unwrap();
+        let expected = full_layout(&root, &mut layout);
+        for (node, full) in root.iter().zip(expected.iter()) {
+            assert_eq!(node.id, full.id);
+            assert!(
+                (node.center.x - full.center.x).abs() < 1e-6,
+                "node {} at x = {} instead of {}",
+                node.id,
+                node.center.x,
+                full.center.x
+            );
+            assert!(
+                (node.center.y - full.center.y).abs() < 1e-6,
+                "node {} at y = {} instead of {}",
+                node.id,
+                node.center.y,
+                full.center.y
+            );
+        }
+    }
+});
@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    adapters::{label_height, label_width},
+    Coordinate, TreeInfo,
+};
+
+/// The files and directories under a path, sorted by name.
+///
+/// Entries that can not be read are drawn as leaves, symbolic links are not followed.
+#[derive(Clone, Debug)]
+pub struct FileTree {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    hidden: bool,
+}
+
+/// A file or directory with its depth below the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileNode {
+    /// path of the entry, joined to the root
+    pub path: PathBuf,
+    /// 0 for the root
+    pub depth: usize,
+}
+
+impl FileTree {
+    /// The tree under a path, hidden entries are skipped.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), max_depth: None, hidden: false }
+    }
+    /// Set how many levels below the root are listed, all levels by default.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth: Some(max_depth), ..self }
+    }
+    /// Set whether entries starting with `.` are listed.
+    pub fn with_hidden(self, hidden: bool) -> Self {
+        Self { hidden, ..self }
+    }
+    /// The file name, directories end with `/`.
+    pub fn label(&self, node: &FileNode) -> String {
+        let name = match node.path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => node.path.to_string_lossy().into_owned(),
+        };
+        if is_dir(&node.path) {
+            format!("{}/", name)
+        }
+        else {
+            name
+        }
+    }
+}
+
+fn is_dir(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|meta| meta.is_dir())
+}
+
+impl TreeInfo for FileTree {
+    type Node = FileNode;
+
+    fn root(&self) -> Self::Node {
+        FileNode { path: self.root.clone(), depth: 0 }
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let mut paths = vec![];
+        let listed = self.max_depth.is_none_or(|max| node.depth < max);
+        if listed && is_dir(&node.path) {
+            if let Ok(entries) = node.path.read_dir() {
+                paths = entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| self.hidden || !entry.file_name().to_string_lossy().starts_with('.'))
+                    .map(|entry| entry.path())
+                    .collect();
+            }
+        }
+        paths.sort();
+        let depth = node.depth + 1;
+        paths.into_iter().map(move |path| FileNode { path, depth })
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        label_width(&self.label(node))
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        label_height(&self.label(node))
+    }
+}
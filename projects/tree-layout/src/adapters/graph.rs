@@ -0,0 +1,95 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use petgraph::{
+    graph::{IndexType, NodeIndex},
+    visit::EdgeRef,
+    EdgeType, Graph,
+};
+
+use crate::{
+    adapters::{label_height, label_width},
+    Coordinate, TreeInfo,
+};
+
+/// The nodes of a graph reachable from a root, as the breadth first spanning tree.
+///
+/// Edges are followed in their direction in a directed graph, every node is drawn once under the first node that
+/// reaches it, so cycles and shared nodes are cut.
+#[derive(Debug)]
+pub struct GraphTree<'g, N, E, Ty, Ix>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    graph: &'g Graph<N, E, Ty, Ix>,
+    root: NodeIndex<Ix>,
+    children: Vec<Vec<NodeIndex<Ix>>>,
+}
+
+impl<'g, N, E, Ty, Ix> GraphTree<'g, N, E, Ty, Ix>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    /// The tree of the nodes reachable from `root`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root` is not a node of the graph.
+    pub fn new(graph: &'g Graph<N, E, Ty, Ix>, root: NodeIndex<Ix>) -> Self {
+        assert!(root.index() < graph.node_count(), "the root is not a node of the graph");
+        let mut children = vec![vec![]; graph.node_count()];
+        let mut visited = vec![false; graph.node_count()];
+        visited[root.index()] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            // petgraph lists the newest edge first, children keep the order the edges were added in
+            let mut edges: Vec<_> = graph.edges(node).collect();
+            edges.sort_by_key(|edge| edge.id());
+            for edge in edges {
+                let child = if edge.source() == node { edge.target() } else { edge.source() };
+                if !std::mem::replace(&mut visited[child.index()], true) {
+                    children[node.index()].push(child);
+                    queue.push_back(child);
+                }
+            }
+        }
+        Self { graph, root, children }
+    }
+    /// The weight of a node, used as its label.
+    pub fn label(&self, node: &NodeIndex<Ix>) -> String
+    where
+        N: Display,
+    {
+        self.graph[*node].to_string()
+    }
+}
+
+impl<'g, N, E, Ty, Ix> TreeInfo for GraphTree<'g, N, E, Ty, Ix>
+where
+    N: Display,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    type Node = NodeIndex<Ix>;
+
+    fn root(&self) -> Self::Node {
+        self.root
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        self.children[node.index()].clone().into_iter()
+    }
+
+    fn count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        label_width(&self.label(node))
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        label_height(&self.label(node))
+    }
+}
@@ -0,0 +1,81 @@
+use serde_json::Value;
+
+use crate::{
+    adapters::{label_height, label_width},
+    Coordinate, TreeInfo,
+};
+
+/// A json document, objects and arrays are nodes and every other value is a leaf.
+#[derive(Clone, Copy, Debug)]
+pub struct JsonTree<'a> {
+    root: &'a Value,
+}
+
+/// A value of the document with the key it is stored under.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JsonNode<'a> {
+    /// field or index in the parent
+    pub key: JsonKey<'a>,
+    /// the value itself
+    pub value: &'a Value,
+}
+
+/// Where a value is stored in its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonKey<'a> {
+    /// the whole document
+    Root,
+    /// a field of an object
+    Field(&'a str),
+    /// an item of an array
+    Index(usize),
+}
+
+impl<'a> JsonTree<'a> {
+    /// The tree of a document.
+    pub fn new(root: &'a Value) -> Self {
+        Self { root }
+    }
+    /// `key: value` for scalars, `key {n}` for objects and `key [n]` for arrays of `n` values.
+    pub fn label(&self, node: &JsonNode<'a>) -> String {
+        let value = match node.value {
+            Value::Object(object) => format!("{{{}}}", object.len()),
+            Value::Array(array) => format!("[{}]", array.len()),
+            scalar => scalar.to_string(),
+        };
+        match node.key {
+            JsonKey::Root => value,
+            JsonKey::Field(field) => format!("{}: {}", field, value),
+            JsonKey::Index(index) => format!("{}: {}", index, value),
+        }
+    }
+}
+
+impl<'a> TreeInfo for JsonTree<'a> {
+    type Node = JsonNode<'a>;
+
+    fn root(&self) -> Self::Node {
+        JsonNode { key: JsonKey::Root, value: self.root }
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let children: Vec<JsonNode<'a>> = match node.value {
+            Value::Object(object) => {
+                object.iter().map(|(field, value)| JsonNode { key: JsonKey::Field(field), value }).collect()
+            }
+            Value::Array(array) => {
+                array.iter().enumerate().map(|(index, value)| JsonNode { key: JsonKey::Index(index), value }).collect()
+            }
+            _ => vec![],
+        };
+        children.into_iter()
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        label_width(&self.label(node))
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        label_height(&self.label(node))
+    }
+}
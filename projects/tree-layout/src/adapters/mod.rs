@@ -0,0 +1,46 @@
+//! Ready made [`TreeInfo`](crate::TreeInfo) for common tree sources, each behind its own feature.
+//!
+//! - `fs`: `FileTree`, a directory and its entries
+//! - `graph`: `GraphTree`, the spanning tree of a petgraph graph
+//! - `json`: `JsonTree`, the values of a json document
+//! - `nested`: `Nested`, nested lists and s-expressions
+//!
+//! The adapters know no font, a node is sized as if every character of its label were [`CHAR_WIDTH`] wide.
+
+use crate::Coordinate;
+
+#[cfg(feature = "fs")]
+pub use self::fs::{FileNode, FileTree};
+#[cfg(feature = "graph")]
+pub use self::graph::GraphTree;
+#[cfg(feature = "json")]
+pub use self::json::{JsonKey, JsonNode, JsonTree};
+#[cfg(feature = "nested")]
+pub use self::nested::{Nested, SExprError};
+
+#[cfg(feature = "fs")]
+mod fs;
+#[cfg(feature = "graph")]
+mod graph;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "nested")]
+mod nested;
+
+/// Width of a character of a label
+pub const CHAR_WIDTH: Coordinate = 8.0;
+/// Height of a node
+pub const NODE_HEIGHT: Coordinate = 20.0;
+/// Space between the label and the left and right border
+pub const NODE_PADDING: Coordinate = 6.0;
+
+/// Width of a node with this label, wide enough for its longest line.
+pub fn label_width(label: &str) -> Coordinate {
+    let chars = label.lines().map(|line| line.chars().count()).max().unwrap_or(0).max(1);
+    chars as Coordinate * CHAR_WIDTH + 2.0 * NODE_PADDING
+}
+
+/// Height of a node with this label, one [`NODE_HEIGHT`] per line.
+pub fn label_height(label: &str) -> Coordinate {
+    label.lines().count().max(1) as Coordinate * NODE_HEIGHT
+}
@@ -0,0 +1,123 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    adapters::{label_height, label_width},
+    Coordinate, TreeInfo,
+};
+
+/// Nested lists, every list is a node and every item a leaf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Nested<T> {
+    /// an item, drawn with its `Display`
+    Leaf(T),
+    /// a list, drawn as `()`
+    List(Vec<Nested<T>>),
+}
+
+impl<T> From<Vec<Nested<T>>> for Nested<T> {
+    fn from(list: Vec<Nested<T>>) -> Self {
+        Self::List(list)
+    }
+}
+
+impl<T: Display> Nested<T> {
+    /// The item of a leaf, `()` for a list.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Leaf(item) => item.to_string(),
+            Self::List(_) => "()".to_string(),
+        }
+    }
+}
+
+impl<'a, T: Display> TreeInfo for &'a Nested<T> {
+    type Node = &'a Nested<T>;
+
+    fn root(&self) -> Self::Node {
+        self
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let node: &'a Nested<T> = node;
+        match node {
+            Nested::Leaf(_) => [].iter(),
+            Nested::List(list) => list.iter(),
+        }
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        label_width(&node.label())
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        label_height(&node.label())
+    }
+}
+
+/// Error of parsing an S-expression into a [`Nested<String>`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SExprError {
+    /// A list is not closed, at the byte offset of its `(`
+    Unclosed(usize),
+    /// A `)` closes no list, at its byte offset
+    Unopened(usize),
+    /// The text has no expression or more than one, at the byte offset of the second
+    NotOne(usize),
+}
+
+impl Display for SExprError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unclosed(offset) => write!(f, "unclosed `(` at {}", offset),
+            Self::Unopened(offset) => write!(f, "unexpected `)` at {}", offset),
+            Self::NotOne(offset) => write!(f, "expected exactly one expression, found another at {}", offset),
+        }
+    }
+}
+
+impl Error for SExprError {}
+
+/// Atoms are split on whitespace and parentheses, `;` starts a comment to the end of the line.
+impl FromStr for Nested<String> {
+    type Err = SExprError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the open lists with the offsets of their `(`, the outermost collects the top level expressions
+        let mut stack: Vec<(usize, Vec<Nested<String>>)> = vec![(0, vec![])];
+        let mut chars = s.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            // where the expression ending here started
+            let mut start = offset;
+            match c {
+                '(' => stack.push((offset, vec![])),
+                ')' if stack.len() == 1 => return Err(SExprError::Unopened(offset)),
+                ')' => {
+                    let (open, list) = stack.pop().unwrap_or_default();
+                    start = open;
+                    stack.last_mut().unwrap().1.push(Nested::List(list));
+                }
+                ';' => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+                c if c.is_whitespace() => {}
+                _ => {
+                    let mut end = offset + c.len_utf8();
+                    while let Some((i, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && !"();".contains(*c)) {
+                        end = i + c.len_utf8();
+                    }
+                    stack.last_mut().unwrap().1.push(Nested::Leaf(s[offset..end].to_string()));
+                }
+            }
+            if stack.len() == 1 && stack[0].1.len() > 1 {
+                return Err(SExprError::NotOne(start));
+            }
+        }
+        match stack.pop() {
+            Some((offset, _)) if !stack.is_empty() => Err(SExprError::Unclosed(offset)),
+            Some((_, mut top)) if top.len() == 1 => Ok(top.remove(0)),
+            _ => Err(SExprError::NotOne(s.len())),
+        }
+    }
+}
@@ -1,19 +1,33 @@
-use crate::{Coordinate, LayoutConfig, LayoutNode, Point, Traverse, TreeInfo};
+use crate::{Coordinate, LayoutConfig, LayoutError, LayoutNode, Point, Traverse, TreeInfo};
 use shape_core::Line;
 use std::collections::{BTreeMap, HashMap};
 
 pub struct TreeArena<T: TreeInfo> {
     arena: Vec<T::Node>,
-    root: LayoutNode,
+    /// Boxed so that the parent links of its children survive moving the arena
+    root: Box<LayoutNode>,
     tree: std::marker::PhantomData<T>,
 }
 
 impl<T: TreeInfo> TreeArena<T> {
+    /// Build and layout the tree, constraints that cannot be met are skipped.
+    ///
+    /// Constraints and sizes are best effort, see [`LayoutConfig::layout`].
     pub fn build(tree: T, layout: &LayoutConfig) -> Self {
+        let mut out = Self::insert_tree(tree);
+        layout.clone().layout(&mut out.root);
+        out
+    }
+    /// Build and layout the tree, see [`LayoutConfig::try_layout`].
+    pub fn try_build(tree: T, layout: &LayoutConfig) -> Result<Self, LayoutError> {
+        let mut out = Self::insert_tree(tree);
+        layout.clone().try_layout(&mut out.root)?;
+        Ok(out)
+    }
+    fn insert_tree(tree: T) -> Self {
         let mut out = Self { arena: Vec::with_capacity(tree.count()), root: Default::default(), tree: Default::default() };
-        out.root = out.insert_node(tree.root(), &tree);
-        let mut config = layout.clone();
-        config.layout(&mut out.root);
+        out.root = Box::new(out.insert_node(tree.root(), &tree));
+        out.root.reset_parent_link_of_children();
         out
     }
     fn insert_node(&mut self, parent: T::Node, tree: &T) -> LayoutNode {
@@ -0,0 +1,61 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{ConstraintError, Coordinate};
+
+/// The reason why a tree cannot be laid out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutError {
+    /// The width or height of a node is NaN, infinite or negative
+    InvalidSize {
+        /// id of the node
+        id: usize,
+        /// width of the node
+        width: Coordinate,
+        /// height of the node
+        height: Coordinate,
+    },
+    /// A margin of the [`LayoutConfig`](crate::LayoutConfig) is NaN, infinite or negative
+    InvalidMargin {
+        /// the rejected margin
+        margin: Coordinate,
+    },
+    /// A changed node passed to a partial layout is not attached to the root
+    DanglingNode {
+        /// index of the node in the changed nodes
+        index: usize,
+    },
+    /// The contour of a subtree ended early, the layout data of the tree is out of sync
+    BrokenContour {
+        /// id of the node whose children are being separated
+        id: usize,
+    },
+    /// The position constraints cannot be met
+    Constraint(ConstraintError),
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize { id, width, height } => write!(f, "node {} has an invalid size {} x {}", id, width, height),
+            Self::InvalidMargin { margin } => write!(f, "margin {} must be finite and non-negative", margin),
+            Self::DanglingNode { index } => write!(f, "changed node at {} is not attached to the root", index),
+            Self::BrokenContour { id } => write!(f, "contour of the children of node {} ended early", id),
+            Self::Constraint(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Constraint(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConstraintError> for LayoutError {
+    fn from(value: ConstraintError) -> Self {
+        Self::Constraint(value)
+    }
+}
@@ -1,11 +1,11 @@
-use std::ptr::NonNull;
+use std::{collections::HashMap, ptr::NonNull};
 
 use num::Float;
 use tinyset::SetUsize;
 
-use crate::{node::LayoutData, utils::erase_lifetime, Coordinate, LayoutNode};
+use crate::{node::LayoutData, utils::erase_lifetime, Coordinate, LayoutError, LayoutNode};
 
-use super::linked_y_list::LinkedYList;
+use super::{constraint::PositionConstraint, linked_y_list::LinkedYList, order::ChildOrder};
 
 #[derive(Clone)]
 pub struct LayoutConfig {
@@ -16,11 +16,35 @@ pub struct LayoutConfig {
     pub is_transpose: bool,
     /// only for layered layout
     pub depth_to_y: Vec<Coordinate>,
+    /// horizontal constraints, see [`PositionConstraint`]
+    pub constraints: Vec<PositionConstraint>,
+    /// width of a column in [`PositionConstraint::Column`]
+    pub column_width: Coordinate,
+    /// extra space in front of a child, keyed by node address
+    pub(super) sibling_gap: HashMap<usize, Coordinate>,
+    /// how children are ordered, see [`ChildOrder`]
+    pub child_order: ChildOrder,
+    /// children orders chosen by the last layout, keyed by node address
+    pub(super) permutations: HashMap<usize, Vec<usize>>,
+    /// the error the last infallible layout recovered from
+    pub(super) last_error: Option<LayoutError>,
 }
 
 impl LayoutConfig {
     pub fn new(margin: Coordinate, peer_margin: Coordinate) -> Self {
-        LayoutConfig { margin, peer_margin, is_layered: false, is_transpose: false, depth_to_y: vec![] }
+        LayoutConfig {
+            margin,
+            peer_margin,
+            is_layered: false,
+            is_transpose: false,
+            depth_to_y: vec![],
+            constraints: vec![],
+            column_width: 1.0,
+            sibling_gap: HashMap::new(),
+            child_order: ChildOrder::Preserve,
+            permutations: HashMap::new(),
+            last_error: None,
+        }
     }
     pub fn with_layered(self, layered: bool) -> Self {
         Self { is_layered: layered, ..self }
@@ -31,20 +55,22 @@ struct Contour {
     is_left: bool,
     pub current: Option<NonNull<LayoutNode>>,
     modifier_sum: Coordinate,
+    /// id of the parent whose children are separated
+    owner: usize,
 }
 
 impl Contour {
-    pub fn new(is_left: bool, current: &LayoutNode) -> Self {
-        Contour { is_left, current: Some(current.into()), modifier_sum: current.get_layout().modifier_to_subtree }
+    pub fn new(is_left: bool, current: &LayoutNode, owner: usize) -> Self {
+        Contour { is_left, current: Some(current.into()), modifier_sum: current.get_layout().modifier_to_subtree, owner }
     }
 
-    fn node(&self) -> &LayoutNode {
+    fn node(&self) -> Result<&LayoutNode, LayoutError> {
         match self.current {
             Some(node) => {
                 let node = unsafe { node.as_ref() };
-                node
+                Ok(node)
             }
-            None => panic!(),
+            None => Err(LayoutError::BrokenContour { id: self.owner }),
         }
     }
 
@@ -52,14 +78,14 @@ impl Contour {
         self.current.is_none()
     }
 
-    pub fn left(&self) -> Coordinate {
-        let node = self.node();
-        self.modifier_sum + node.relative_x - node.width / 2.
+    pub fn left(&self) -> Result<Coordinate, LayoutError> {
+        let node = self.node()?;
+        Ok(self.modifier_sum + node.relative_x - node.width / 2.)
     }
 
-    pub fn right(&self) -> Coordinate {
-        let node = self.node();
-        self.modifier_sum + node.relative_x + node.width / 2.
+    pub fn right(&self) -> Result<Coordinate, LayoutError> {
+        let node = self.node()?;
+        Ok(self.modifier_sum + node.relative_x + node.width / 2.)
     }
 
     pub fn bottom(&self) -> Coordinate {
@@ -76,28 +102,23 @@ impl Contour {
         if let Some(mut current) = self.current {
             let node = unsafe { current.as_mut() };
             if self.is_left {
-                if !node.children.is_empty() {
-                    self.current = Some((&**node.children.first().unwrap()).into());
-                    let node = self.node();
-                    self.modifier_sum += node.layout_data.as_ref().unwrap().modifier_to_subtree;
+                if let Some(first) = node.children.first() {
+                    self.current = Some((&**first).into());
+                    self.modifier_sum += first.get_layout().modifier_to_subtree;
                 }
                 else {
                     self.modifier_sum += node.get_layout().modifier_thread_left;
                     self.current = node.get_layout().thread_left;
                 }
             }
-            else if !node.children.is_empty() {
-                self.current = Some((&**node.children.last().unwrap()).into());
-                let node = self.node();
-                self.modifier_sum += node.layout_data.as_ref().unwrap().modifier_to_subtree;
+            else if let Some(last) = node.children.last() {
+                self.current = Some((&**last).into());
+                self.modifier_sum += last.get_layout().modifier_to_subtree;
             }
             else {
                 self.modifier_sum += node.get_layout().modifier_thread_right;
                 self.current = node.get_layout().thread_right;
             }
-            if self.current.is_some() {
-                let _ = self.node();
-            }
         }
     }
 }
@@ -105,7 +126,7 @@ impl Contour {
 impl LayoutNode {
     fn set_extreme(&mut self) {
         let self_ptr: NonNull<LayoutNode> = self.into();
-        let tidy = self.layout_data.as_mut().unwrap();
+        let tidy = self.layout_data.as_deref_mut().expect("layout data is initialized before the walks");
         if self.children.is_empty() {
             tidy.extreme_left = Some(self_ptr);
             tidy.extreme_right = Some(self_ptr);
@@ -113,21 +134,21 @@ impl LayoutNode {
             tidy.modifier_extreme_right = 0.;
         }
         else {
-            let first = self.children.first().unwrap().layout_data.as_ref().unwrap();
+            let first = self.children.first().unwrap().get_layout();
             tidy.extreme_left = first.extreme_left;
             tidy.modifier_extreme_left = first.modifier_to_subtree + first.modifier_extreme_left;
-            let last = self.children.last().unwrap().layout_data.as_ref().unwrap();
+            let last = self.children.last().unwrap().get_layout();
             tidy.extreme_right = last.extreme_right;
             tidy.modifier_extreme_right = last.modifier_to_subtree + last.modifier_extreme_right;
         }
     }
 
     fn extreme_left(&mut self) -> &mut LayoutNode {
-        unsafe { self.layout_data.as_mut().unwrap().extreme_left.as_mut().unwrap().as_mut() }
+        unsafe { self.mut_layout().extreme_left.as_mut().unwrap().as_mut() }
     }
 
     fn extreme_right(&mut self) -> &mut LayoutNode {
-        unsafe { self.layout_data.as_mut().unwrap().extreme_right.as_mut().unwrap().as_mut() }
+        unsafe { self.mut_layout().extreme_right.as_mut().unwrap().as_mut() }
     }
 
     fn position_root(&mut self) {
@@ -156,23 +177,23 @@ impl LayoutNode {
 }
 
 impl LayoutConfig {
-    fn separate(&mut self, node: &mut LayoutNode, child_index: usize, mut y_list: LinkedYList) -> LinkedYList {
+    fn separate(
+        &mut self,
+        node: &mut LayoutNode,
+        child_index: usize,
+        mut y_list: LinkedYList,
+    ) -> Result<LinkedYList, LayoutError> {
         // right contour of the left
-        let mut left = Contour::new(false, &node.children[child_index - 1]);
+        let mut left = Contour::new(false, &node.children[child_index - 1], node.id);
         // left contour of the right
-        let mut right = Contour::new(true, &node.children[child_index]);
+        let mut right = Contour::new(true, &node.children[child_index], node.id);
+        let gap = self.peer_margin + self.sibling_gap(&node.children[child_index]);
         while !left.is_none() && !right.is_none() {
             if left.bottom() > y_list.bottom() {
-                let b = y_list.bottom();
-                let top = y_list.pop();
-                if top.is_none() {
-                    println!("Err\n\n{}\n\nleft.bottom={}\nyList.bottom={}", node.str(), left.bottom(), b);
-                }
-
-                y_list = top.unwrap();
+                y_list = y_list.pop().ok_or(LayoutError::BrokenContour { id: node.id })?;
             }
 
-            let dist = left.right() - right.left() + self.peer_margin;
+            let dist = left.right()? - right.left()? + gap;
             if dist > 0. {
                 // left and right are too close. move right part with distance of dist
                 right.modifier_sum += dist;
@@ -190,13 +211,13 @@ impl LayoutConfig {
         }
 
         if left.is_none() && !right.is_none() {
-            self.set_left_thread(node, child_index, right.node(), right.modifier_sum);
+            self.set_left_thread(node, child_index, right.node()?, right.modifier_sum);
         }
         else if !left.is_none() && right.is_none() {
-            self.set_right_thread(node, child_index, left.node(), left.modifier_sum);
+            self.set_right_thread(node, child_index, left.node()?, left.modifier_sum);
         }
 
-        y_list
+        Ok(y_list)
     }
 
     fn set_left_thread(&mut self, node: &mut LayoutNode, current_index: usize, target: &LayoutNode, modifier: Coordinate) {
@@ -276,50 +297,52 @@ impl LayoutConfig {
         };
     }
 
-    fn first_walk(&mut self, node: &mut LayoutNode) {
+    fn first_walk(&mut self, node: &mut LayoutNode) -> Result<(), LayoutError> {
         if node.children.is_empty() {
             node.set_extreme();
-            return;
+            return Ok(());
         }
 
-        self.first_walk(node.children.first_mut().unwrap());
+        self.first_walk(node.children.first_mut().unwrap())?;
         let mut y_list = LinkedYList::new(0, node.children[0].extreme_right().lowest_y());
         for i in 1..node.children.len() {
             let current_child = node.children.get_mut(i).unwrap();
-            self.first_walk(current_child);
+            self.first_walk(current_child)?;
             let max_y = current_child.extreme_left().lowest_y();
-            y_list = self.separate(node, i, y_list);
+            y_list = self.separate(node, i, y_list)?;
             y_list = y_list.update(i, max_y);
         }
 
         node.position_root();
         node.set_extreme();
+        Ok(())
     }
 
-    fn first_walk_with_filter(&mut self, node: &mut LayoutNode, set: &SetUsize) {
+    fn first_walk_with_filter(&mut self, node: &mut LayoutNode, set: &SetUsize) -> Result<(), LayoutError> {
         if !set.contains(node as *const _ as usize) {
             invalidate_extreme_thread(node);
-            return;
+            return Ok(());
         }
 
         if node.children.is_empty() {
             node.set_extreme();
-            return;
+            return Ok(());
         }
 
-        self.first_walk_with_filter(node.children.first_mut().unwrap(), set);
+        self.first_walk_with_filter(node.children.first_mut().unwrap(), set)?;
         let mut y_list = LinkedYList::new(0, node.children[0].extreme_right().lowest_y());
         for i in 1..node.children.len() {
             let current_child = node.children.get_mut(i).unwrap();
             current_child.mut_layout().modifier_to_subtree = -current_child.relative_x;
-            self.first_walk_with_filter(current_child, set);
+            self.first_walk_with_filter(current_child, set)?;
             let max_y = current_child.extreme_left().lowest_y();
-            y_list = self.separate(node, i, y_list);
+            y_list = self.separate(node, i, y_list)?;
             y_list = y_list.update(i, max_y);
         }
 
         node.position_root();
         node.set_extreme();
+        Ok(())
     }
 
     fn second_walk(&mut self, node: &mut LayoutNode, mut mod_sum: Coordinate) {
@@ -349,25 +372,90 @@ impl LayoutConfig {
 }
 
 impl LayoutConfig {
+    /// Layout the tree, constraints that cannot be met are skipped.
+    ///
+    /// Constraints are best effort here: the tree keeps the closest valid layout found and the
+    /// [`ConstraintError`](super::ConstraintError) is kept in [`LayoutConfig::last_error`]. NaN, infinite or negative
+    /// sizes are set to zero and such margins are taken as zero, use [`LayoutConfig::try_layout`] to reject them.
     pub fn layout(&mut self, root: &mut LayoutNode) {
+        clamp_sizes(root);
+        let result = self.with_clamped_margins(|config| config.full_layout(root));
+        self.last_error = result.err();
+    }
+
+    /// Layout the tree, fails on invalid sizes, invalid margins or constraints that cannot be met.
+    ///
+    /// When only the constraints fail, the tree still holds a valid, non-overlapping layout. Children are reordered
+    /// first unless [`LayoutConfig::child_order`] is [`ChildOrder::Preserve`].
+    pub fn try_layout(&mut self, root: &mut LayoutNode) -> Result<(), LayoutError> {
+        self.check_margins()?;
+        check_sizes(root)?;
+        self.full_layout(root)
+    }
+
+    /// The error the last [`LayoutConfig::layout`] or [`LayoutConfig::partial_layout`] recovered from, such as the
+    /// constraints that could not be met, `None` if the layout is exact.
+    pub fn last_error(&self) -> Option<&LayoutError> {
+        self.last_error.as_ref()
+    }
+
+    fn full_layout(&mut self, root: &mut LayoutNode) -> Result<(), LayoutError> {
+        self.order_children(root)?;
+        self.constrained_layout(root)
+    }
+
+    pub(super) fn tidy_layout(&mut self, root: &mut LayoutNode) -> Result<(), LayoutError> {
         root.pre_order_traversal_mut(init_node);
         self.set_y_recursive(root);
-        self.first_walk(root);
+        self.first_walk(root)?;
         self.second_walk(root, 0.);
+        Ok(())
     }
 
-    pub fn partial_layout(&mut self, root: &mut crate::LayoutNode, changed: &[std::ptr::NonNull<crate::LayoutNode>]) {
-        // not implemented for layered
-        if self.is_layered {
-            self.layout(root);
-            return;
+    /// Layout the tree again after the `changed` nodes were inserted or resized.
+    ///
+    /// Changed nodes that are not attached to the root are skipped, invalid sizes and margins are taken as zero as in
+    /// [`LayoutConfig::layout`]. The tree is laid out from scratch if the layout data of the tree is out of sync.
+    pub fn partial_layout(&mut self, root: &mut LayoutNode, changed: &[NonNull<LayoutNode>]) {
+        let changed: Vec<_> = changed.iter().copied().filter(|node| is_attached(root, *node)).collect();
+        for node in changed.iter() {
+            clamp_sizes(unsafe { &mut *node.as_ptr() });
+        }
+        let result = self.with_clamped_margins(|config| config.relayout(root, &changed));
+        self.last_error = match result {
+            Err(LayoutError::BrokenContour { .. }) => self.with_clamped_margins(|config| config.full_layout(root)).err(),
+            result => result.err(),
+        };
+    }
+
+    /// Layout the tree again after the `changed` nodes were inserted or resized.
+    ///
+    /// Only the subtrees of the changed nodes are validated. Every changed node is checked by its parent links up to
+    /// the root, so it must still be alive: a freed node can not be told apart without scanning the whole tree.
+    pub fn try_partial_layout(&mut self, root: &mut LayoutNode, changed: &[NonNull<LayoutNode>]) -> Result<(), LayoutError> {
+        self.check_margins()?;
+        for (index, node) in changed.iter().enumerate() {
+            if !is_attached(root, *node) {
+                return Err(LayoutError::DanglingNode { index });
+            }
+            check_sizes(unsafe { node.as_ref() })?;
+        }
+        self.relayout(root, changed)
+    }
+
+    fn relayout(&mut self, root: &mut LayoutNode, changed: &[NonNull<LayoutNode>]) -> Result<(), LayoutError> {
+        // not implemented for layered, constrained and reordered layout
+        if self.is_layered || !self.constraints.is_empty() || self.child_order != ChildOrder::Preserve {
+            return self.full_layout(root);
         }
 
         for node in changed.iter() {
             let node = unsafe { &mut *node.as_ptr() };
-            if node.layout_data.is_none() {
-                init_node(node);
-            }
+            node.pre_order_traversal_mut(|node| {
+                if node.layout_data.is_none() {
+                    init_node(node);
+                }
+            });
 
             // TODO: can be lazy
             self.set_y_recursive(node);
@@ -377,18 +465,92 @@ impl LayoutConfig {
         for node in changed.iter() {
             set.insert(node.as_ptr() as usize);
             let mut node = unsafe { &mut *node.as_ptr() };
-            while node.parent.is_some() {
+            // the extremes of every node in the set are rebuilt by the walk, only a changed leaf keeps the
+            // threads of its old contour. The extremes of inner nodes may point into removed siblings.
+            if node.children.is_empty() {
                 invalidate_extreme_thread(node);
+            }
+            while node.parent.is_some() {
                 set.insert(node.parent.unwrap().as_ptr() as usize);
                 node = node.mut_parent().unwrap();
             }
         }
 
-        self.first_walk_with_filter(root, &set);
+        self.first_walk_with_filter(root, &set)?;
         // TODO: this can be optimized with onscreen detection,
         // then all nodes' absolute x position can be evaluate lazily
         self.second_walk_with_filter(root, 0., &set);
+        Ok(())
+    }
+
+    fn check_margins(&self) -> Result<(), LayoutError> {
+        for margin in [self.margin, self.peer_margin] {
+            if !is_valid_length(margin) {
+                return Err(LayoutError::InvalidMargin { margin });
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the layout with invalid margins taken as zero, the margins of the config are kept
+    fn with_clamped_margins<F>(&mut self, f: F) -> Result<(), LayoutError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), LayoutError>,
+    {
+        let margins = (self.margin, self.peer_margin);
+        self.margin = clamp_length(self.margin);
+        self.peer_margin = clamp_length(self.peer_margin);
+        let result = f(self);
+        (self.margin, self.peer_margin) = margins;
+        result
+    }
+}
+
+fn is_valid_length(value: Coordinate) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+fn clamp_length(value: Coordinate) -> Coordinate {
+    if is_valid_length(value) {
+        value
+    }
+    else {
+        0.0
+    }
+}
+
+fn check_sizes(root: &LayoutNode) -> Result<(), LayoutError> {
+    let mut error = None;
+    root.pre_order_traversal(|node| {
+        if error.is_none() && !(is_valid_length(node.width) && is_valid_length(node.height)) {
+            error = Some(LayoutError::InvalidSize { id: node.id, width: node.width, height: node.height });
+        }
+    });
+    error.map_or(Ok(()), Err)
+}
+
+fn clamp_sizes(root: &mut LayoutNode) {
+    root.pre_order_traversal_mut(|node| {
+        node.width = clamp_length(node.width);
+        node.height = clamp_length(node.height);
+    });
+}
+
+/// Whether the node is reached from the root, every parent link is checked against the children of the parent
+fn is_attached(root: &LayoutNode, node: NonNull<LayoutNode>) -> bool {
+    let mut node = node.as_ptr() as *const LayoutNode;
+    while !std::ptr::eq(node, root) {
+        let Some(parent) = (unsafe { &*node }).parent
+        else {
+            return false;
+        };
+        let parent = unsafe { parent.as_ref() };
+        if !parent.children.iter().any(|child| std::ptr::eq(&**child, node)) {
+            return false;
+        }
+        node = parent;
     }
+    true
 }
 
 fn init_node(node: &mut LayoutNode) {
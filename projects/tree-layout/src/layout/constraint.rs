@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ptr::NonNull,
+};
+
+use crate::{Coordinate, LayoutConfig, LayoutError, LayoutNode};
+
+/// Upper bound of layout passes spent on widening gaps before giving up.
+const MAX_ITERATIONS: usize = 128;
+const EPSILON: Coordinate = 1e-6;
+
+/// A horizontal constraint on a node, the node is addressed by [`LayoutNode::id`].
+///
+/// Constraints are met by widening the space in front of sibling subtrees, the tidy layout never reorders
+/// children or pulls subtrees closer than `peer_margin`, so the output never overlaps.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionConstraint {
+    /// Pin the center of the node to an absolute x
+    Fixed {
+        /// id of the pinned node
+        id: usize,
+        /// absolute x of the node center
+        x: Coordinate,
+    },
+    /// Pin the center of the node to `column * column_width`
+    Column {
+        /// id of the pinned node
+        id: usize,
+        /// column index, may be negative
+        column: isize,
+    },
+    /// Keep the center of the node at the same x as another node
+    Align {
+        /// id of the aligned node
+        id: usize,
+        /// id of the node to align with
+        target: usize,
+    },
+}
+
+/// The reason why the constraints of a [`LayoutConfig`] cannot be satisfied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintError {
+    /// A constraint refers to an id that is not in the tree
+    MissingNode {
+        /// id of the missing node
+        id: usize,
+    },
+    /// A constraint refers to an id that is shared by several nodes
+    AmbiguousNode {
+        /// id of the duplicated node
+        id: usize,
+    },
+    /// The node is pinned, directly or through alignments, to two different positions
+    Conflict {
+        /// id of the pinned node
+        id: usize,
+        /// position required by an earlier constraint
+        expected: Coordinate,
+        /// position required by this constraint
+        found: Coordinate,
+    },
+    /// The nodes can only be placed by reordering children or by overlapping subtrees
+    Unsatisfiable {
+        /// id of the node that must move to the left
+        left: usize,
+        /// id of the node that must move to the right
+        right: usize,
+    },
+}
+
+impl Display for ConstraintError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingNode { id } => write!(f, "constrained node {} does not exist", id),
+            Self::AmbiguousNode { id } => write!(f, "constrained node {} is not unique", id),
+            Self::Conflict { id, expected, found } => {
+                write!(f, "node {} is pinned to both x = {} and x = {}", id, expected, found)
+            }
+            Self::Unsatisfiable { left, right } => {
+                write!(f, "node {} cannot be placed relative to node {} without reordering or overlapping", right, left)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+/// Constraints resolved into groups of nodes sharing one x.
+struct ConstraintPlan {
+    /// `(a, b, distance)` requires `b.x - a.x == distance`
+    relations: Vec<(usize, usize, Coordinate)>,
+    /// a node and its absolute x, used to translate the whole tree at the end
+    anchor: Option<(usize, Coordinate)>,
+}
+
+impl ConstraintPlan {
+    fn resolve(constraints: &[PositionConstraint], column_width: Coordinate) -> Result<Self, ConstraintError> {
+        // union-find over aligned nodes
+        let mut parent: HashMap<usize, usize> = HashMap::new();
+        fn find(parent: &mut HashMap<usize, usize>, id: usize) -> usize {
+            let up = *parent.entry(id).or_insert(id);
+            if up == id {
+                return id;
+            }
+            let root = find(parent, up);
+            parent.insert(id, root);
+            root
+        }
+        let mut order = vec![];
+        for constraint in constraints {
+            match *constraint {
+                PositionConstraint::Fixed { id, .. } | PositionConstraint::Column { id, .. } => order.push(id),
+                PositionConstraint::Align { id, target } => {
+                    order.push(id);
+                    order.push(target);
+                    let (a, b) = (find(&mut parent, id), find(&mut parent, target));
+                    parent.insert(b, a);
+                }
+            }
+        }
+        let mut pinned: HashMap<usize, (usize, Coordinate)> = HashMap::new();
+        let mut anchors = vec![];
+        for constraint in constraints {
+            let (id, x) = match *constraint {
+                PositionConstraint::Fixed { id, x } => (id, x),
+                PositionConstraint::Column { id, column } => (id, column as Coordinate * column_width),
+                PositionConstraint::Align { .. } => continue,
+            };
+            let group = find(&mut parent, id);
+            match pinned.get(&group) {
+                Some(&(_, expected)) if (expected - x).abs() > EPSILON => {
+                    return Err(ConstraintError::Conflict { id, expected, found: x });
+                }
+                Some(_) => {}
+                None => {
+                    pinned.insert(group, (id, x));
+                    anchors.push((id, x));
+                }
+            }
+        }
+        let mut relations = vec![];
+        let mut leaders: HashMap<usize, usize> = HashMap::new();
+        order.dedup();
+        for id in order {
+            let group = find(&mut parent, id);
+            match leaders.get(&group) {
+                Some(&leader) if leader != id => relations.push((leader, id, 0.0)),
+                Some(_) => {}
+                None => {
+                    leaders.insert(group, id);
+                }
+            }
+        }
+        let anchor = anchors.first().copied();
+        if let Some((first, x0)) = anchor {
+            for &(id, x) in anchors.iter().skip(1) {
+                relations.push((first, id, x - x0));
+            }
+        }
+        Ok(Self { relations, anchor })
+    }
+}
+
+impl LayoutConfig {
+    /// Add a constraint to the layout, see [`PositionConstraint`].
+    pub fn with_constraint(mut self, constraint: PositionConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+    /// Set the width of a column used by [`PositionConstraint::Column`].
+    pub fn with_column_width(self, column_width: Coordinate) -> Self {
+        Self { column_width, ..self }
+    }
+    /// Widen the gaps between siblings until every constraint is met.
+    pub(super) fn constrained_layout(&mut self, root: &mut LayoutNode) -> Result<(), LayoutError> {
+        self.sibling_gap.clear();
+        self.tidy_layout(root)?;
+        if self.constraints.is_empty() {
+            return Ok(());
+        }
+        let plan = ConstraintPlan::resolve(&self.constraints, self.column_width)?;
+        let nodes = find_constrained(root, &self.constraints)?;
+        for _ in 0..MAX_ITERATIONS {
+            let mut settled = true;
+            for &(a, b, distance) in plan.relations.iter() {
+                let (a, b) = (nodes[&a], nodes[&b]);
+                let error = unsafe { distance - (b.as_ref().center.x - a.as_ref().center.x) };
+                if error.abs() <= EPSILON {
+                    continue;
+                }
+                settled = false;
+                let (left, right, delta) = if error > 0.0 { (a, b, error) } else { (b, a, -error) };
+                if !self.widen(left, right, delta) {
+                    let (left, right) = unsafe { (left.as_ref().id, right.as_ref().id) };
+                    return Err(ConstraintError::Unsatisfiable { left, right }.into());
+                }
+            }
+            if settled {
+                if let Some((id, x)) = plan.anchor {
+                    let shift = x - unsafe { nodes[&id].as_ref().center.x };
+                    root.pre_order_traversal_mut(|node| node.center.x += shift);
+                }
+                return Ok(());
+            }
+            self.tidy_layout(root)?;
+        }
+        // report a relation the last pass still violates, not just the first one
+        let violated = plan.relations.iter().find(|&&(a, b, distance)| {
+            let (a, b) = (nodes[&a], nodes[&b]);
+            unsafe { (distance - (b.as_ref().center.x - a.as_ref().center.x)).abs() > EPSILON }
+        });
+        let &(a, b, distance) = violated.unwrap_or(&plan.relations[0]);
+        let moved = unsafe { nodes[&b].as_ref().center.x - nodes[&a].as_ref().center.x };
+        let (left, right) = if distance > moved { (a, b) } else { (b, a) };
+        Err(ConstraintError::Unsatisfiable { left, right }.into())
+    }
+
+    /// Extra space required in front of the node, on top of `peer_margin`.
+    pub(super) fn sibling_gap(&self, node: &LayoutNode) -> Coordinate {
+        self.sibling_gap.get(&(node as *const _ as usize)).copied().unwrap_or(0.0)
+    }
+
+    /// Grow one gap so that `right` moves to the right relative to `left`, returns `false` if no gap helps.
+    fn widen(&mut self, left: NonNull<LayoutNode>, right: NonNull<LayoutNode>, delta: Coordinate) -> bool {
+        let left_path = ancestors(left);
+        let right_path = ancestors(right);
+        let common = left_path.iter().zip(right_path.iter()).take_while(|(a, b)| a == b).count();
+        let target = match (left_path.get(common), right_path.get(common)) {
+            // separated at the common ancestor, only a right sibling can be pushed away
+            (Some(&l), Some(&r)) => {
+                if child_index(l) < child_index(r) {
+                    Some((r, delta))
+                }
+                else {
+                    None
+                }
+            }
+            // `left` is an ancestor, push `right` away from the first sibling on its path
+            (None, Some(_)) => {
+                right_path[common..].iter().find(|&&node| child_index(node) > 0).map(|&node| (node, 2.0 * delta))
+            }
+            // `right` is an ancestor, push the next sibling on the path of `left` away
+            (Some(_), None) => left_path[common..].iter().find_map(|&node| next_sibling(node)).map(|node| (node, 2.0 * delta)),
+            (None, None) => None,
+        };
+        match target {
+            Some((node, gap)) => {
+                *self.sibling_gap.entry(node.as_ptr() as usize).or_insert(0.0) += gap;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn find_constrained(
+    root: &LayoutNode,
+    constraints: &[PositionConstraint],
+) -> Result<HashMap<usize, NonNull<LayoutNode>>, ConstraintError> {
+    let mut wanted: HashMap<usize, Vec<NonNull<LayoutNode>>> = HashMap::new();
+    for constraint in constraints {
+        match *constraint {
+            PositionConstraint::Fixed { id, .. } | PositionConstraint::Column { id, .. } => {
+                wanted.insert(id, vec![]);
+            }
+            PositionConstraint::Align { id, target } => {
+                wanted.insert(id, vec![]);
+                wanted.insert(target, vec![]);
+            }
+        }
+    }
+    root.pre_order_traversal(|node| {
+        if let Some(found) = wanted.get_mut(&node.id) {
+            found.push(node.into());
+        }
+    });
+    let mut out = HashMap::with_capacity(wanted.len());
+    for (id, found) in wanted {
+        match found.as_slice() {
+            [] => return Err(ConstraintError::MissingNode { id }),
+            [node] => {
+                out.insert(id, *node);
+            }
+            _ => return Err(ConstraintError::AmbiguousNode { id }),
+        }
+    }
+    Ok(out)
+}
+
+/// Path from the root down to the node, both included.
+fn ancestors(node: NonNull<LayoutNode>) -> Vec<NonNull<LayoutNode>> {
+    let mut path = vec![node];
+    let mut current = unsafe { node.as_ref() };
+    while let Some(parent) = current.parent {
+        path.push(parent);
+        current = unsafe { parent.as_ref() };
+    }
+    path.reverse();
+    path
+}
+
+fn child_index(node: NonNull<LayoutNode>) -> usize {
+    let node = unsafe { node.as_ref() };
+    match node.get_parent() {
+        Some(parent) => parent.children.iter().position(|child| std::ptr::eq(&**child, node)).unwrap_or(0),
+        None => 0,
+    }
+}
+
+fn next_sibling(node: NonNull<LayoutNode>) -> Option<NonNull<LayoutNode>> {
+    let parent = unsafe { node.as_ref() }.get_parent()?;
+    parent.children.get(child_index(node) + 1).map(|child| (&**child).into())
+}
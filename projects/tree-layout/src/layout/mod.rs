@@ -1,9 +1,13 @@
 mod config;
+mod constraint;
 mod linked_y_list;
+mod order;
 
 pub use crate::node::basic_layout::BoundingBox;
 use crate::{Coordinate, LayoutNode};
 pub use config::LayoutConfig;
+pub use constraint::{ConstraintError, PositionConstraint};
+pub use order::ChildOrder;
 use std::{collections::HashMap, ptr::NonNull};
 
 pub struct TreeLayout {
@@ -79,6 +83,7 @@ impl TreeLayout {
         }
     }
 
+    /// Layout the tree, constraints are best effort, see [`LayoutConfig::layout`].
     pub fn layout(&mut self) {
         if self.is_empty() {
             return;
@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+
+use crate::{Coordinate, LayoutConfig, LayoutError, LayoutNode};
+
+const EPSILON: Coordinate = 1e-6;
+
+/// How children are ordered before the layout, for trees whose child order has no meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildOrder {
+    /// Keep the order of the input, required for ordered trees such as a CST
+    #[default]
+    Preserve,
+    /// Reorder children to minimize the width of the bounding box
+    MinimizeWidth,
+    /// Reorder children to minimize the area covered by the rows of the tree, see [`ChildOrder::cost`]
+    MinimizeArea,
+}
+
+impl ChildOrder {
+    /// Cost of a laid out subtree, lower is better.
+    ///
+    /// The area of a subtree is the sum over its depths of the horizontal span of the row times the tallest node in
+    /// the row. Unlike the bounding box it rewards subtrees whose deep rows stay narrow.
+    pub fn cost(&self, root: &LayoutNode) -> Coordinate {
+        let mut rows = vec![];
+        collect_rows(root, 0, &mut rows);
+        self.rows_cost(&rows)
+    }
+    fn rows_cost(&self, rows: &[Row]) -> Coordinate {
+        match self {
+            Self::Preserve => 0.0,
+            Self::MinimizeWidth => {
+                let left = rows.iter().map(|row| row.0).fold(Coordinate::INFINITY, Coordinate::min);
+                let right = rows.iter().map(|row| row.1).fold(Coordinate::NEG_INFINITY, Coordinate::max);
+                right - left
+            }
+            Self::MinimizeArea => rows.iter().map(|(min, max, height)| (max - min) * height).sum(),
+        }
+    }
+}
+
+/// `(left, right, height)` of the nodes at one depth
+type Row = (Coordinate, Coordinate, Coordinate);
+
+fn collect_rows(node: &LayoutNode, depth: usize, rows: &mut Vec<Row>) {
+    let (left, right) = (node.center.x - node.width / 2.0, node.center.x + node.width / 2.0);
+    merge_row(rows, depth, (left, right, node.height));
+    for child in node.children.iter() {
+        collect_rows(child, depth + 1, rows);
+    }
+}
+
+fn merge_row(rows: &mut Vec<Row>, depth: usize, (left, right, height): Row) {
+    match rows.get_mut(depth) {
+        Some(row) => {
+            row.0 = row.0.min(left);
+            row.1 = row.1.max(right);
+            row.2 = row.2.max(height);
+        }
+        None => rows.push((left, right, height)),
+    }
+}
+
+impl LayoutConfig {
+    /// Set how children are ordered, see [`ChildOrder`].
+    pub fn with_child_order(self, child_order: ChildOrder) -> Self {
+        Self { child_order, ..self }
+    }
+    /// The order chosen for the children of this node during the last layout.
+    ///
+    /// `permutation[i]` is the index, before the layout, of the child now at index `i`. Returns `None` if the children
+    /// kept their order.
+    pub fn permutation(&self, node: &LayoutNode) -> Option<&[usize]> {
+        self.permutations.get(&(node as *const _ as usize)).map(|p| p.as_slice())
+    }
+    /// Reorder the children of every node bottom up.
+    ///
+    /// Candidate orders are compared by the rows of the subtrees packed side by side, so every subtree is measured
+    /// once. The input order is restored if the tidy layout of the reordered tree turns out more costly.
+    pub(super) fn order_children(&mut self, root: &mut LayoutNode) -> Result<(), LayoutError> {
+        self.permutations.clear();
+        if self.child_order == ChildOrder::Preserve {
+            return Ok(());
+        }
+        self.sibling_gap.clear();
+        self.tidy_layout(root)?;
+        let before = self.child_order.cost(root);
+        self.order_subtree(root);
+        if self.permutations.is_empty() {
+            return Ok(());
+        }
+        self.tidy_layout(root)?;
+        if self.child_order.cost(root) > before + EPSILON {
+            let permutations = std::mem::take(&mut self.permutations);
+            root.pre_order_traversal_mut(|node| {
+                if let Some(permutation) = permutations.get(&(node as *const _ as usize)) {
+                    permute(node, &inverse(permutation));
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Order the children of the subtree, returns its rows relative to the center of the node.
+    fn order_subtree(&mut self, node: &mut LayoutNode) -> Vec<Row> {
+        let mut children: Vec<Vec<Row>> = node.children.iter_mut().map(|child| self.order_subtree(child)).collect();
+        // two children only mirror each other
+        if children.len() >= 3 {
+            let widths: Vec<Coordinate> = children.iter().map(|rows| ChildOrder::MinimizeWidth.rows_cost(rows)).collect();
+            let mut best: Option<(Coordinate, Vec<usize>)> = None;
+            for candidate in candidates(&widths) {
+                let ordered: Vec<&[Row]> = candidate.iter().map(|&i| children[i].as_slice()).collect();
+                let cost = self.child_order.rows_cost(&self.pack(node, &ordered));
+                match &best {
+                    Some((best, _)) if cost >= best - EPSILON => {}
+                    _ => best = Some((cost, candidate)),
+                }
+            }
+            if let Some((_, permutation)) = best {
+                if permutation.iter().enumerate().any(|(i, &j)| i != j) {
+                    permute(node, &permutation);
+                    let mut taken: Vec<Option<Vec<Row>>> = children.into_iter().map(Some).collect();
+                    children = permutation.iter().map(|&i| taken[i].take().expect("permutation is a bijection")).collect();
+                    self.permutations.insert(node as *const LayoutNode as usize, permutation);
+                }
+            }
+        }
+        let ordered: Vec<&[Row]> = children.iter().map(|rows| rows.as_slice()).collect();
+        self.pack(node, &ordered)
+    }
+
+    /// Rows of the node above its children, each child as far left as its rows allow and the node centered over the
+    /// first and last child like the tidy layout does.
+    fn pack(&self, node: &LayoutNode, children: &[&[Row]]) -> Vec<Row> {
+        let mut below: Vec<Row> = vec![];
+        let mut centers = Vec::with_capacity(children.len());
+        for rows in children {
+            let offset = match centers.is_empty() {
+                true => 0.0,
+                false => below
+                    .iter()
+                    .zip(rows.iter())
+                    .map(|(placed, row)| placed.1 + self.peer_margin - row.0)
+                    .fold(Coordinate::NEG_INFINITY, Coordinate::max),
+            };
+            for (depth, &(left, right, height)) in rows.iter().enumerate() {
+                merge_row(&mut below, depth, (left + offset, right + offset, height));
+            }
+            centers.push(offset);
+        }
+        let shift = match (centers.first(), centers.last()) {
+            (Some(first), Some(last)) => -(first + last) / 2.0,
+            _ => 0.0,
+        };
+        let mut rows = vec![(-node.width / 2.0, node.width / 2.0, node.height)];
+        rows.extend(below.into_iter().map(|(left, right, height)| (left + shift, right + shift, height)));
+        rows
+    }
+}
+
+/// Candidate orders, the input order comes first so that it wins ties.
+fn candidates(widths: &[Coordinate]) -> Vec<Vec<usize>> {
+    let identity: Vec<usize> = (0..widths.len()).collect();
+    let mut ascending = identity.clone();
+    ascending.sort_by(|&a, &b| widths[a].total_cmp(&widths[b]));
+    let descending: Vec<usize> = ascending.iter().rev().copied().collect();
+    // widest in the middle, narrower ones alternating to both sides
+    let mut middle = VecDeque::with_capacity(widths.len());
+    for (i, &index) in descending.iter().enumerate() {
+        if i % 2 == 0 {
+            middle.push_back(index)
+        }
+        else {
+            middle.push_front(index)
+        }
+    }
+    // widest on the outside, so that narrow subtrees can tuck under their neighbours
+    let mut outside = VecDeque::with_capacity(widths.len());
+    for (i, &index) in ascending.iter().enumerate() {
+        if i % 2 == 0 {
+            outside.push_back(index)
+        }
+        else {
+            outside.push_front(index)
+        }
+    }
+    // alternate wide and narrow subtrees
+    let mut alternate = Vec::with_capacity(widths.len());
+    let (mut low, mut high) = (0, widths.len());
+    while low < high {
+        high -= 1;
+        alternate.push(ascending[high]);
+        if low < high {
+            alternate.push(ascending[low]);
+            low += 1;
+        }
+    }
+    vec![identity, ascending, descending, middle.into(), outside.into(), alternate]
+}
+
+/// Move the child at `permutation[i]` to index `i`.
+fn permute(node: &mut LayoutNode, permutation: &[usize]) {
+    let mut children: Vec<Option<Box<LayoutNode>>> = std::mem::take(&mut node.children).into_iter().map(Some).collect();
+    node.children = permutation.iter().map(|&i| children[i].take().expect("permutation is a bijection")).collect();
+}
+
+fn inverse(permutation: &[usize]) -> Vec<usize> {
+    let mut out = vec![0; permutation.len()];
+    for (i, &j) in permutation.iter().enumerate() {
+        out[j] = i;
+    }
+    out
+}
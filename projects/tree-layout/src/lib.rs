@@ -3,18 +3,23 @@
 
 pub use crate::{
     arena::{ArenaIterator, TreeArena},
-    layout::{LayoutConfig, TreeLayout},
+    errors::LayoutError,
+    layout::{ChildOrder, ConstraintError, LayoutConfig, PositionConstraint, TreeLayout},
     node::LayoutNode,
     traits::TreeInfo,
     traverse::Traverse,
 };
 
+pub mod adapters;
 mod arena;
+mod errors;
 mod layout;
+pub mod metrics;
 mod node;
 mod traits;
 mod traverse;
 mod utils;
+pub mod validate;
 
 pub type Coordinate = f64;
 pub const NULL_ID: usize = usize::MAX;
@@ -0,0 +1,108 @@
+//! Quality metrics of a laid out tree, useful to compare layout modes.
+
+use crate::{
+    validate::{mirror, pre_order_traversal_rev},
+    Coordinate, LayoutConfig, LayoutNode,
+};
+
+/// Summary of a laid out tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutMetrics {
+    /// width of the bounding box of all nodes
+    pub width: Coordinate,
+    /// height of the bounding box of all nodes
+    pub height: Coordinate,
+    /// `width * height`
+    pub area: Coordinate,
+    /// `width / height`, `0` for an empty box
+    pub aspect_ratio: Coordinate,
+    /// mean distance from the bottom of a parent to the top of its children
+    pub mean_edge_length: Coordinate,
+    /// `1` if the layout of the mirrored tree is an exact mirror, down to `0`
+    pub symmetry: Coordinate,
+}
+
+impl LayoutMetrics {
+    /// Measure the tree, `layout` is used to lay out the mirrored copy for the symmetry score.
+    pub fn measure(root: &LayoutNode, layout: &LayoutConfig) -> Self {
+        let (width, height) = bounding_size(root);
+        Self {
+            width,
+            height,
+            area: width * height,
+            aspect_ratio: aspect_ratio(root),
+            mean_edge_length: mean_edge_length(root),
+            symmetry: symmetry_score(root, layout),
+        }
+    }
+}
+
+/// Width and height of the bounding box of all nodes.
+pub fn bounding_size(root: &LayoutNode) -> (Coordinate, Coordinate) {
+    let mut min = (Coordinate::INFINITY, Coordinate::INFINITY);
+    let mut max = (Coordinate::NEG_INFINITY, Coordinate::NEG_INFINITY);
+    root.pre_order_traversal(|node| {
+        min.0 = min.0.min(node.center.x - node.width / 2.0);
+        min.1 = min.1.min(node.center.y - node.height / 2.0);
+        max.0 = max.0.max(node.center.x + node.width / 2.0);
+        max.1 = max.1.max(node.center.y + node.height / 2.0);
+    });
+    (max.0 - min.0, max.1 - min.1)
+}
+
+/// Area of the bounding box of all nodes.
+pub fn area(root: &LayoutNode) -> Coordinate {
+    let (width, height) = bounding_size(root);
+    width * height
+}
+
+/// Width over height of the bounding box, `0` for an empty box.
+pub fn aspect_ratio(root: &LayoutNode) -> Coordinate {
+    let (width, height) = bounding_size(root);
+    if height > 0.0 {
+        width / height
+    }
+    else {
+        0.0
+    }
+}
+
+/// Mean length of the links from the bottom center of a parent to the top center of its children.
+pub fn mean_edge_length(root: &LayoutNode) -> Coordinate {
+    let mut sum = 0.0;
+    let mut count = 0;
+    root.pre_order_traversal(|node| {
+        let start = node.bottom_center();
+        for child in node.children.iter() {
+            let end = child.top_center();
+            sum += (end.x - start.x).hypot(end.y - start.y);
+            count += 1;
+        }
+    });
+    if count == 0 {
+        0.0
+    }
+    else {
+        sum / count as Coordinate
+    }
+}
+
+/// How close the layout is to the mirror of the mirrored tree's layout.
+///
+/// The mean deviation of every node is divided by the width of the tree, `1` means perfectly symmetric.
+pub fn symmetry_score(root: &LayoutNode, layout: &LayoutConfig) -> Coordinate {
+    let mirrored = match mirror(root, layout) {
+        Some(s) => s,
+        None => return 0.0,
+    };
+    let mut origin = vec![];
+    root.pre_order_traversal(|node| origin.push(node.center.x - root.center.x));
+    let mut reflected = vec![];
+    pre_order_traversal_rev(&mirrored, |node| reflected.push(mirrored.center.x - node.center.x));
+    let (width, _) = bounding_size(root);
+    if origin.is_empty() || width <= 0.0 {
+        return 1.0;
+    }
+    let deviation = origin.iter().zip(reflected.iter()).map(|(x, y)| (x - y).abs()).sum::<Coordinate>();
+    (1.0 - deviation / origin.len() as Coordinate / width).clamp(0.0, 1.0)
+}
@@ -151,8 +151,16 @@ impl LayoutNode {
     pub fn mut_layout(&mut self) -> &mut LayoutData {
         self.layout_data.as_mut().unwrap()
     }
+    /// Layout data of the node, `None` before the node has been laid out
+    pub fn try_get_layout(&self) -> Option<&LayoutData> {
+        self.layout_data.as_deref()
+    }
+    /// Mutable layout data of the node, `None` before the node has been laid out
+    pub fn try_mut_layout(&mut self) -> Option<&mut LayoutData> {
+        self.layout_data.as_deref_mut()
+    }
 
-    fn reset_parent_link_of_children(&mut self) {
+    pub(crate) fn reset_parent_link_of_children(&mut self) {
         if self.children.is_empty() {
             return;
         }
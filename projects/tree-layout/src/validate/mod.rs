@@ -0,0 +1,179 @@
+//! Aesthetic rules of a tidy tree layout, each check returns the violations instead of panicking.
+
+use std::{
+    fmt::{Display, Formatter},
+    ptr::NonNull,
+};
+
+use crate::{ChildOrder, Coordinate, LayoutConfig, LayoutNode};
+
+const EPSILON: Coordinate = 1e-6;
+
+/// A broken aesthetic rule, nodes are addressed by [`LayoutNode::id`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// Two nodes overlap
+    Overlap {
+        /// id of the first node
+        first: usize,
+        /// id of the second node
+        second: usize,
+    },
+    /// A child is not to the right of its previous sibling
+    ChildOrder {
+        /// id of the parent
+        parent: usize,
+        /// id of the previous sibling
+        left: usize,
+        /// id of the misplaced child
+        right: usize,
+    },
+    /// Two siblings are not on the same y
+    SiblingLevel {
+        /// id of the parent
+        parent: usize,
+        /// id of the first child
+        first: usize,
+        /// id of the misplaced child
+        child: usize,
+    },
+    /// The node is not mirrored when the children of every node are reversed
+    Asymmetric {
+        /// id of the node
+        id: usize,
+        /// x relative to the root
+        x: Coordinate,
+        /// negated x relative to the root in the mirrored layout
+        mirrored: Coordinate,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overlap { first, second } => write!(f, "node {} and node {} overlap", first, second),
+            Self::ChildOrder { parent, left, right } => {
+                write!(f, "child {} of node {} is not to the right of child {}", right, parent, left)
+            }
+            Self::SiblingLevel { parent, first, child } => {
+                write!(f, "child {} of node {} is not on the level of child {}", child, parent, first)
+            }
+            Self::Asymmetric { id, x, mirrored } => write!(f, "node {} is at {} but mirrored at {}", id, x, mirrored),
+        }
+    }
+}
+
+/// Run every check, the symmetry check lays out a mirrored copy of the tree with `layout`.
+pub fn check_all(root: &LayoutNode, layout: &LayoutConfig) -> Vec<Violation> {
+    let mut out = find_overlaps(root);
+    out.extend(check_child_order(root));
+    out.extend(check_sibling_level(root));
+    out.extend(check_symmetry(root, layout));
+    out
+}
+
+/// Find every pair of overlapping nodes.
+pub fn find_overlaps(root: &LayoutNode) -> Vec<Violation> {
+    let mut nodes: Vec<&LayoutNode> = root.iter().collect();
+    nodes.sort_by(|a, b| left_edge(a).total_cmp(&left_edge(b)));
+    let mut out = vec![];
+    // sweep from left to right, only nodes whose right edge is beyond the current left edge can intersect
+    let mut active: Vec<&LayoutNode> = vec![];
+    for node in nodes {
+        active.retain(|other| right_edge(other) > left_edge(node));
+        for other in active.iter() {
+            if node.intersects(other) {
+                out.push(Violation::Overlap { first: other.id, second: node.id });
+            }
+        }
+        active.push(node);
+    }
+    out
+}
+
+/// Check that children are placed from left to right.
+pub fn check_child_order(root: &LayoutNode) -> Vec<Violation> {
+    let mut out = vec![];
+    root.pre_order_traversal(|node| {
+        for pair in node.children.windows(2) {
+            if pair[0].center.x >= pair[1].center.x {
+                out.push(Violation::ChildOrder { parent: node.id, left: pair[0].id, right: pair[1].id });
+            }
+        }
+    });
+    out
+}
+
+/// Check that siblings share the same y.
+pub fn check_sibling_level(root: &LayoutNode) -> Vec<Violation> {
+    let mut out = vec![];
+    root.pre_order_traversal(|node| {
+        if let Some(first) = node.children.first() {
+            for child in node.children.iter().skip(1) {
+                if child.center.y != first.center.y {
+                    out.push(Violation::SiblingLevel { parent: node.id, first: first.id, child: child.id });
+                }
+            }
+        }
+    });
+    out
+}
+
+/// Check that the layout of the mirrored tree is the mirror of the layout.
+///
+/// Constraints of `layout` are ignored, pinned nodes break the symmetry on purpose.
+pub fn check_symmetry(root: &LayoutNode, layout: &LayoutConfig) -> Vec<Violation> {
+    let mirrored = match mirror(root, layout) {
+        Some(s) => s,
+        None => return vec![],
+    };
+    let mut origin = vec![];
+    root.pre_order_traversal(|node| origin.push((node.id, node.center.x - root.center.x)));
+    let mut reflected = vec![];
+    pre_order_traversal_rev(&mirrored, |node| reflected.push(mirrored.center.x - node.center.x));
+    origin
+        .into_iter()
+        .zip(reflected)
+        .filter(|((_, x), mirrored)| (x - mirrored).abs() > EPSILON)
+        .map(|((id, x), mirrored)| Violation::Asymmetric { id, x, mirrored })
+        .collect()
+}
+
+/// Copy of the tree with the children of every node reversed, laid out with `layout`.
+///
+/// The children of `root` are already in the order `layout` chose, the copy keeps them mirrored as they are.
+pub(crate) fn mirror(root: &LayoutNode, layout: &LayoutConfig) -> Option<Box<LayoutNode>> {
+    let mut mirrored = Box::new(root.clone());
+    mirrored.pre_order_traversal_mut(|node| {
+        node.children.reverse();
+        node.reset_parent_link_of_children();
+    });
+    let mut layout = layout.clone();
+    layout.constraints.clear();
+    layout.child_order = ChildOrder::Preserve;
+    layout.try_layout(&mut mirrored).ok()?;
+    Some(mirrored)
+}
+
+/// Pre-order traversal visiting the first child first, the opposite of [`LayoutNode::pre_order_traversal`].
+pub(crate) fn pre_order_traversal_rev<F>(node: &LayoutNode, mut f: F)
+where
+    F: FnMut(&LayoutNode),
+{
+    let mut stack: Vec<NonNull<LayoutNode>> = vec![node.into()];
+    while let Some(node) = stack.pop() {
+        let node = unsafe { node.as_ref() };
+        f(node);
+        for child in node.children.iter().rev() {
+            stack.push(child.as_ref().into());
+        }
+    }
+}
+
+fn left_edge(node: &LayoutNode) -> Coordinate {
+    node.center.x - node.width / 2.0
+}
+
+fn right_edge(node: &LayoutNode) -> Coordinate {
+    node.center.x + node.width / 2.0
+}
@@ -0,0 +1,118 @@
+use super::*;
+use tree_layout::{adapters, TreeArena, TreeInfo};
+
+/// The labels of a tree, depth first with the depth of each node
+fn outline<T: TreeInfo>(tree: &T, label: impl Fn(&T::Node) -> String) -> Vec<(usize, String)> {
+    fn walk<T: TreeInfo>(
+        tree: &T,
+        node: T::Node,
+        depth: usize,
+        label: &dyn Fn(&T::Node) -> String,
+        out: &mut Vec<(usize, String)>,
+    ) {
+        out.push((depth, label(&node)));
+        for child in tree.children(&node) {
+            walk(tree, child, depth + 1, label, out);
+        }
+    }
+    let mut out = vec![];
+    walk(tree, tree.root(), 0, &label, &mut out);
+    out
+}
+
+#[test]
+fn test_label_size() {
+    assert_eq!(adapters::label_width("abc"), 3. * adapters::CHAR_WIDTH + 2. * adapters::NODE_PADDING);
+    assert_eq!(adapters::label_width("a\nbcd"), adapters::label_width("bcd"));
+    assert_eq!(adapters::label_height("a\nb"), 2. * adapters::NODE_HEIGHT);
+    assert!(adapters::label_width("") > 0.);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    use adapters::JsonTree;
+    let value = serde_json::json!({"name": "tree", "sizes": [1, 2], "empty": null});
+    let tree = JsonTree::new(&value);
+    let labels = outline(&tree, |node| tree.label(node));
+    assert_eq!(
+        labels,
+        vec![
+            (0, "{3}".to_string()),
+            (1, "empty: null".to_string()),
+            (1, "name: \"tree\"".to_string()),
+            (1, "sizes: [2]".to_string()),
+            (2, "0: 1".to_string()),
+            (2, "1: 2".to_string()),
+        ]
+    );
+    let arena = TreeArena::build(tree, &LayoutConfig::new(10., 10.));
+    assert_eq!((&arena).into_iter().count(), 6);
+}
+
+#[cfg(feature = "graph")]
+#[test]
+fn test_graph() {
+    use adapters::GraphTree;
+    use petgraph::Graph;
+    let mut graph = Graph::<&str, ()>::new();
+    let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.add_node(name));
+    // a cycle and an edge into the root, both cut
+    graph.extend_with_edges([(a, b), (a, c), (b, d), (c, d), (d, a)]);
+    let tree = GraphTree::new(&graph, a);
+    let labels = outline(&tree, |node| tree.label(node));
+    assert_eq!(labels, vec![(0, "a".to_string()), (1, "b".to_string()), (2, "d".to_string()), (1, "c".to_string())]);
+    let tree = GraphTree::new(&graph, c);
+    let labels = outline(&tree, |node| tree.label(node));
+    assert_eq!(labels.len(), 4);
+    assert_eq!(labels[0], (0, "c".to_string()));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_directory() {
+    use adapters::FileTree;
+    let root = std::env::temp_dir().join(format!("tree-layout-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src/nested")).unwrap();
+    for file in ["Cargo.toml", "src/lib.rs", "src/nested/mod.rs", ".hidden"] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+    let tree = FileTree::new(&root);
+    let labels: Vec<_> = outline(&tree, |node| tree.label(node)).into_iter().skip(1).collect();
+    let hidden = outline(&FileTree::new(&root).with_hidden(true), |node| tree.label(node));
+    let shallow = FileTree::new(&root).with_max_depth(1);
+    let shallow = outline(&shallow, |node| tree.label(node));
+    std::fs::remove_dir_all(&root).unwrap();
+    assert_eq!(
+        labels,
+        vec![
+            (1, "Cargo.toml".to_string()),
+            (1, "src/".to_string()),
+            (2, "lib.rs".to_string()),
+            (2, "nested/".to_string()),
+            (3, "mod.rs".to_string()),
+        ]
+    );
+    assert_eq!(hidden[1], (1, ".hidden".to_string()));
+    assert_eq!(shallow.len(), 3);
+}
+
+#[cfg(feature = "nested")]
+#[test]
+fn test_sexpr() {
+    use adapters::{Nested, SExprError};
+    let sexpr: Nested<String> = "(define (square x) ; comment\n  (* x x))".parse().unwrap();
+    let labels = outline(&&sexpr, |node| node.label());
+    let depths: Vec<usize> = labels.iter().map(|(depth, _)| *depth).collect();
+    assert_eq!(depths, vec![0, 1, 1, 2, 2, 1, 2, 2, 2]);
+    assert_eq!(labels[1].1, "define");
+    assert_eq!(labels[6].1, "*");
+    assert_eq!("(a (b)".parse::<Nested<String>>(), Err(SExprError::Unclosed(0)));
+    assert_eq!("a)".parse::<Nested<String>>(), Err(SExprError::Unopened(1)));
+    assert_eq!("a (b)".parse::<Nested<String>>(), Err(SExprError::NotOne(2)));
+    assert_eq!("".parse::<Nested<String>>(), Err(SExprError::NotOne(0)));
+
+    let lists = Nested::List(vec![Nested::Leaf(1), Nested::List(vec![Nested::Leaf(2), Nested::Leaf(3)])]);
+    let arena = TreeArena::build(&lists, &LayoutConfig::new(10., 10.));
+    assert_eq!((&arena).into_iter().count(), 5);
+}
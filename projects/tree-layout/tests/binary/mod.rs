@@ -37,21 +37,34 @@ impl BinaryNode {
 impl TreeInfo for BinaryTree {
     type Node = BinaryNode;
 
-    fn root(&self) -> &Self::Node {
-        &self.root
+    fn root(&self) -> Self::Node {
+        self.root.clone()
     }
 
-    fn children<'a>(&self, node: &'a Self::Node) -> impl Iterator<Item = &'a Self::Node> {
-        node.left.iter().chain(node.right.iter()).map(move |x| &**x)
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        node.left.iter().chain(node.right.iter()).map(|x| x.as_ref().clone())
     }
 }
 
 #[test]
 fn test() {
     let tree = BinaryTree::random(10086, 0.4);
-    let arena = TreeArena::build(&BinaryTree::random(224, 0.4), &LayoutConfig::new(20.0, 20.0));
+    let arena = TreeArena::build(BinaryTree::random(224, 0.4), &LayoutConfig::new(20.0, 20.0));
     println!("{:?}", tree);
-    for i in arena.into_iter() {
+    for i in (&arena).into_iter() {
         println!("{:?}", i);
     }
 }
+
+#[test]
+fn test_links_survive_move() {
+    let arena = TreeArena::try_build(BinaryTree::random(10086, 0.4), &LayoutConfig::new(20.0, 20.0)).unwrap();
+    let moved = [arena];
+    for (node, _) in &moved[0] {
+        assert_eq!(moved[0].get_link(node).is_some(), node.id != 0);
+        if let Some(parent) = node.get_parent() {
+            assert!(parent.children.iter().any(|child| child.id == node.id));
+            assert!(parent.bottom_center().y < node.top_center().y);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+use tree_layout::{ConstraintError, LayoutError, PositionConstraint};
+
+fn sample() -> Box<LayoutNode> {
+    let mut root = Box::new(LayoutNode::new(0, 4., 1.));
+    root.append_child(LayoutNode::new_with_children(
+        1,
+        2.,
+        1.,
+        vec![LayoutNode::new(10, 3., 1.), LayoutNode::new(11, 3., 1.), LayoutNode::new(12, 1., 1.)],
+    ));
+    root.append_child(LayoutNode::new_with_child(2, 2., 1., LayoutNode::new(20, 5., 1.)));
+    root.append_child(LayoutNode::new(3, 2., 1.));
+    root
+}
+
+fn center_x(root: &LayoutNode, id: usize) -> Coordinate {
+    root.iter().find(|node| node.id == id).unwrap().center.x
+}
+
+#[test]
+fn test_fixed_pin() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.).with_constraint(PositionConstraint::Fixed { id: 11, x: 100. });
+    layout.try_layout(&mut root).unwrap();
+    assert!((center_x(&root, 11) - 100.).abs() < 1e-6);
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+    aesthetic_rules::check_nodes_order(&root);
+}
+
+#[test]
+fn test_column_pins() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.)
+        .with_column_width(10.)
+        .with_constraint(PositionConstraint::Column { id: 1, column: 0 })
+        .with_constraint(PositionConstraint::Column { id: 3, column: 3 });
+    layout.try_layout(&mut root).unwrap();
+    assert!(center_x(&root, 1).abs() < 1e-6);
+    assert!((center_x(&root, 3) - 30.).abs() < 1e-6);
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+    aesthetic_rules::check_nodes_order(&root);
+}
+
+#[test]
+fn test_align() {
+    let mut root = Box::new(LayoutNode::new(0, 4., 1.));
+    root.append_child(LayoutNode::new_with_children(1, 2., 1., vec![LayoutNode::new(10, 6., 1.), LayoutNode::new(11, 6., 1.)]));
+    root.append_child(LayoutNode::new(2, 1., 1.));
+    let mut layout = LayoutConfig::new(1., 1.).with_constraint(PositionConstraint::Align { id: 11, target: 2 });
+    layout.try_layout(&mut root).unwrap();
+    assert!((center_x(&root, 11) - center_x(&root, 2)).abs() < 1e-6);
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+    aesthetic_rules::check_nodes_order(&root);
+    // a cousin can not be pulled under the subtree of its left neighbour
+    let mut layout = LayoutConfig::new(1., 1.).with_constraint(PositionConstraint::Align { id: 10, target: 2 });
+    assert_eq!(
+        layout.try_layout(&mut root),
+        Err(LayoutError::Constraint(ConstraintError::Unsatisfiable { left: 2, right: 10 }))
+    );
+}
+
+#[test]
+fn test_align_with_descendant() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.).with_constraint(PositionConstraint::Align { id: 12, target: 0 });
+    layout.try_layout(&mut root).unwrap();
+    assert!((center_x(&root, 12) - center_x(&root, 0)).abs() < 1e-6);
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+}
+
+#[test]
+fn test_unsatisfiable() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.)
+        .with_constraint(PositionConstraint::Fixed { id: 1, x: 10. })
+        .with_constraint(PositionConstraint::Fixed { id: 3, x: 0. });
+    assert_eq!(
+        layout.try_layout(&mut root),
+        Err(LayoutError::Constraint(ConstraintError::Unsatisfiable { left: 3, right: 1 }))
+    );
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+    // the best effort layout keeps the violation
+    layout.layout(&mut root);
+    assert_eq!(layout.last_error(), Some(&LayoutError::Constraint(ConstraintError::Unsatisfiable { left: 3, right: 1 })));
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+
+    let mut layout = LayoutConfig::new(1., 1.)
+        .with_constraint(PositionConstraint::Fixed { id: 1, x: 0. })
+        .with_constraint(PositionConstraint::Align { id: 1, target: 2 })
+        .with_constraint(PositionConstraint::Fixed { id: 2, x: 5. });
+    assert!(matches!(layout.try_layout(&mut root), Err(LayoutError::Constraint(ConstraintError::Conflict { id: 2, .. }))));
+
+    let mut layout = LayoutConfig::new(1., 1.).with_constraint(PositionConstraint::Fixed { id: 42, x: 0. });
+    assert_eq!(layout.try_layout(&mut root), Err(LayoutError::Constraint(ConstraintError::MissingNode { id: 42 })));
+}
+
+#[test]
+fn test_random_alignment() {
+    let mut rng = StdRng::seed_from_u64(3001);
+    for _ in 0..20 {
+        let mut tree = gen_tree(&mut rng, 50);
+        let leaves: Vec<usize> = tree.iter().filter(|node| node.children.is_empty()).map(|node| node.id).collect();
+        let (first, last) = (leaves[0], leaves[leaves.len() - 1]);
+        let mut layout = LayoutConfig::new(10., 10.).with_constraint(PositionConstraint::Align { id: first, target: last });
+        if layout.try_layout(&mut tree).is_ok() {
+            assert!((center_x(&tree, first) - center_x(&tree, last)).abs() < 1e-6);
+        }
+        aesthetic_rules::assert_no_overlap_nodes(&tree);
+        aesthetic_rules::check_nodes_order(&tree);
+    }
+}
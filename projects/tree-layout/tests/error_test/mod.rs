@@ -0,0 +1,65 @@
+use super::*;
+use tree_layout::LayoutError;
+
+#[test]
+fn test_invalid_size() {
+    let mut layout = LayoutConfig::new(10., 10.);
+    for (width, height) in [(Coordinate::NAN, 1.), (1., Coordinate::INFINITY), (-1., 1.)] {
+        let mut root = Box::new(LayoutNode::new(0, 1., 1.));
+        root.append_child(LayoutNode::new(1, 1., 1.));
+        root.append_child(LayoutNode::new(2, width, height));
+        match layout.try_layout(&mut root) {
+            Err(LayoutError::InvalidSize { id, .. }) => assert_eq!(id, 2),
+            other => panic!("{:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_clamped_layout() {
+    let mut root = Box::new(LayoutNode::new(0, 1., 1.));
+    root.append_child(LayoutNode::new(1, Coordinate::NAN, 1.));
+    root.append_child(LayoutNode::new(2, 1., -1.));
+    let mut layout = LayoutConfig::new(Coordinate::INFINITY, 10.);
+    layout.layout(&mut root);
+    assert_eq!(layout.last_error(), None);
+    assert_eq!((root.children[0].width, root.children[1].height), (0., 0.));
+    assert!(root.iter().all(|node| node.center.x.is_finite() && node.center.y.is_finite()));
+    assert!(layout.margin.is_infinite());
+
+    let node = root.children[0].append_child(LayoutNode::new(3, 1., Coordinate::NAN));
+    let mut detached = Box::new(LayoutNode::new(4, 1., 1.));
+    layout.partial_layout(&mut root, &[node, NonNull::from(&mut *detached)]);
+    assert_eq!(root.children[0].children[0].height, 0.);
+    aesthetic_rules::assert_no_overlap_nodes(&root);
+}
+
+#[test]
+fn test_invalid_margin() {
+    let mut root = Box::new(LayoutNode::new(0, 1., 1.));
+    let mut layout = LayoutConfig::new(10., -1.);
+    assert_eq!(layout.try_layout(&mut root), Err(LayoutError::InvalidMargin { margin: -1. }));
+}
+
+#[test]
+fn test_partial_layout_errors() {
+    let mut rng = StdRng::seed_from_u64(1001);
+    let mut layout = LayoutConfig::new(10., 10.);
+    let mut tree = gen_tree(&mut rng, 20);
+    layout.try_layout(&mut tree).unwrap();
+
+    let mut detached = Box::new(LayoutNode::new(7, 1., 1.));
+    let changed = [NonNull::from(&mut *detached)];
+    assert_eq!(layout.try_partial_layout(&mut tree, &changed), Err(LayoutError::DanglingNode { index: 0 }));
+    // a removed subtree still links to its old parent
+    let mut removed = tree.children.pop().unwrap();
+    let changed = [NonNull::from(&mut *tree.children[0]), NonNull::from(&mut *removed)];
+    assert_eq!(layout.try_partial_layout(&mut tree, &changed), Err(LayoutError::DanglingNode { index: 1 }));
+
+    let node = tree.children[0].append_child(LayoutNode::new(8, Coordinate::NAN, 1.));
+    assert!(matches!(layout.try_partial_layout(&mut tree, &[node]), Err(LayoutError::InvalidSize { id: 8, .. })));
+
+    unsafe { &mut *node.as_ptr() }.width = 4.;
+    layout.try_partial_layout(&mut tree, &[node]).unwrap();
+    aesthetic_rules::assert_no_overlap_nodes(&tree);
+}
@@ -2,11 +2,17 @@
 #![allow(soft_unstable, unused)]
 extern crate test;
 
+mod adapters;
 mod aesthetic_rules;
 mod binary;
+mod constraint_test;
+mod error_test;
 mod generator;
 mod layout_bench;
 mod layout_test;
+mod metrics_test;
+mod order_test;
+mod partial_layout_test;
 
 use rand::prelude::*;
 
@@ -0,0 +1,54 @@
+use super::*;
+use tree_layout::{
+    metrics::{self, LayoutMetrics},
+    validate::{self, Violation},
+};
+
+#[test]
+fn test_metrics() {
+    let mut root = Box::new(LayoutNode::new(0, 2., 1.));
+    root.append_child(LayoutNode::new(1, 2., 1.));
+    root.append_child(LayoutNode::new(2, 2., 1.));
+    let mut layout = LayoutConfig::new(1., 2.);
+    layout.layout(&mut root);
+    let metrics = LayoutMetrics::measure(&root, &layout);
+    assert_eq!(metrics.width, 6.);
+    assert_eq!(metrics.height, 3.);
+    assert_eq!(metrics.area, 18.);
+    assert_eq!(metrics.aspect_ratio, 2.);
+    assert_eq!(metrics.mean_edge_length, 2f64.hypot(1.));
+    assert_eq!(metrics.symmetry, 1.);
+}
+
+#[test]
+fn test_random_metrics() {
+    let mut rng = StdRng::seed_from_u64(1001);
+    let mut layout = LayoutConfig::new(10., 10.);
+    for _ in 0..10 {
+        let mut tree = gen_tree(&mut rng, 100);
+        layout.layout(&mut tree);
+        assert!(validate::check_all(&tree, &layout).is_empty());
+        assert!((metrics::symmetry_score(&tree, &layout) - 1.).abs() < 1e-9);
+        assert!(metrics::area(&tree) > 0.);
+    }
+}
+
+#[test]
+fn test_violations() {
+    let mut root = Box::new(LayoutNode::new(0, 2., 1.));
+    root.append_child(LayoutNode::new(1, 2., 1.));
+    root.append_child(LayoutNode::new(2, 2., 1.));
+    let mut layout = LayoutConfig::new(1., 2.);
+    layout.layout(&mut root);
+    root.children[1].center.x = root.children[0].center.x - 1.;
+    root.children[1].center.y += 0.5;
+    let violations = validate::check_all(&root, &layout);
+    assert!(
+        violations.contains(&Violation::Overlap { first: 1, second: 2 })
+            || violations.contains(&Violation::Overlap { first: 2, second: 1 })
+    );
+    assert!(violations.contains(&Violation::ChildOrder { parent: 0, left: 1, right: 2 }));
+    assert!(violations.contains(&Violation::SiblingLevel { parent: 0, first: 1, child: 2 }));
+    assert!(violations.iter().any(|v| matches!(v, Violation::Asymmetric { .. })));
+    assert!(metrics::symmetry_score(&root, &layout) < 1.);
+}
@@ -0,0 +1,72 @@
+use super::*;
+use tree_layout::{metrics::bounding_size, validate, ChildOrder};
+
+/// A deep subtree with a wide row of grandchildren next to two wide leaves, the row fits under the leaves.
+fn sample() -> Box<LayoutNode> {
+    let mut root = Box::new(LayoutNode::new(0, 1., 1.));
+    root.append_child(LayoutNode::new_with_children(1, 1., 1., (10..13).map(|id| LayoutNode::new(id, 10., 1.)).collect()));
+    root.append_child(LayoutNode::new(2, 12., 1.));
+    root.append_child(LayoutNode::new(3, 12., 1.));
+    root
+}
+
+fn child_ids(node: &LayoutNode) -> Vec<usize> {
+    node.children.iter().map(|child| child.id).collect()
+}
+
+#[test]
+fn test_preserve_by_default() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.);
+    layout.layout(&mut root);
+    assert_eq!(child_ids(&root), vec![1, 2, 3]);
+    assert_eq!(layout.permutation(&root), None);
+}
+
+#[test]
+fn test_minimize_width() {
+    let mut root = sample();
+    let mut layout = LayoutConfig::new(1., 1.);
+    layout.layout(&mut root);
+    let (preserved, _) = bounding_size(&root);
+
+    let mut layout = LayoutConfig::new(1., 1.).with_child_order(ChildOrder::MinimizeWidth);
+    layout.layout(&mut root);
+    let (width, _) = bounding_size(&root);
+    assert!(width < preserved, "{} >= {}", width, preserved);
+    let permutation = layout.permutation(&root).unwrap();
+    let reordered: Vec<usize> = permutation.iter().map(|&i| [1, 2, 3][i]).collect();
+    assert_eq!(child_ids(&root), reordered);
+    assert!(validate::check_all(&root, &layout).is_empty());
+}
+
+#[test]
+fn test_random_reorder() {
+    let mut rng = StdRng::seed_from_u64(3002);
+    for order in [ChildOrder::MinimizeWidth, ChildOrder::MinimizeArea] {
+        for _ in 0..10 {
+            let mut tree = gen_tree(&mut rng, 100);
+            let mut layout = LayoutConfig::new(10., 10.);
+            layout.layout(&mut tree);
+            let before = order.cost(&tree);
+            let mut layout = layout.with_child_order(order);
+            layout.layout(&mut tree);
+            assert!(order.cost(&tree) <= before + 1e-6);
+            aesthetic_rules::assert_no_overlap_nodes(&tree);
+            aesthetic_rules::check_nodes_order(&tree);
+            assert_eq!(validate::check_symmetry(&tree, &layout), vec![]);
+        }
+    }
+}
+
+#[test]
+fn test_permutation_by_node() {
+    // both subtrees use the same ids, their orders are still told apart
+    let mut root = Box::new(LayoutNode::new(0, 1., 1.));
+    root.append_child(*sample());
+    root.append_child(LayoutNode::new_with_children(0, 1., 1., (1..4).map(|id| LayoutNode::new(id, 1., 1.)).collect()));
+    let mut layout = LayoutConfig::new(1., 1.).with_child_order(ChildOrder::MinimizeWidth);
+    layout.layout(&mut root);
+    assert!(layout.permutation(&root.children[0]).is_some());
+    assert_eq!(layout.permutation(&root.children[1]), None);
+}
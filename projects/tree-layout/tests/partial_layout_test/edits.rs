@@ -0,0 +1,99 @@
+//! Tree edits shared by the partial layout property test and the fuzz target.
+
+use std::ptr::NonNull;
+
+use tree_layout::{Coordinate, LayoutConfig, LayoutNode};
+
+/// An edit of the tree, indices are taken modulo the number of nodes so that every sequence is valid.
+#[derive(Clone, Debug)]
+pub enum Edit {
+    Insert { parent: usize, width: u8, height: u8 },
+    Resize { node: usize, width: u8, height: u8 },
+    Remove { node: usize },
+    Reparent { node: usize, parent: usize },
+}
+
+/// Build a tree where the i-th node is a child of one of the nodes before it.
+pub fn build_tree(nodes: &[(usize, u8, u8)]) -> Box<LayoutNode> {
+    let mut root = Box::new(LayoutNode::new(0, 10., 10.));
+    let mut pointers: Vec<NonNull<LayoutNode>> = vec![(&mut *root).into()];
+    for (id, &(parent, width, height)) in nodes.iter().enumerate() {
+        let mut parent = pointers[parent % pointers.len()];
+        let parent = unsafe { parent.as_mut() };
+        pointers.push(parent.append_child(LayoutNode::new(id + 1, width as Coordinate, height as Coordinate)));
+    }
+    root
+}
+
+fn pre_order(root: &mut LayoutNode) -> Vec<NonNull<LayoutNode>> {
+    let mut nodes = vec![];
+    root.pre_order_traversal_mut(|node| nodes.push(node.into()));
+    nodes
+}
+
+fn is_ancestor(ancestor: NonNull<LayoutNode>, node: NonNull<LayoutNode>) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node == ancestor {
+            return true;
+        }
+        current = unsafe { node.as_ref() }.parent;
+    }
+    false
+}
+
+/// Apply the edit and return the nodes that must be passed to `partial_layout`, `None` if the edit is a no-op.
+pub fn apply(root: &mut LayoutNode, edit: &Edit, next_id: &mut usize) -> Option<Vec<NonNull<LayoutNode>>> {
+    let nodes = pre_order(root);
+    // the root can not be removed or moved
+    let non_root = |index: usize| nodes.get(1 + index % nodes.len().checked_sub(1).filter(|n| *n > 0)?).copied();
+    match *edit {
+        Edit::Insert { parent, width, height } => {
+            let parent = unsafe { &mut *nodes[parent % nodes.len()].as_ptr() };
+            *next_id += 1;
+            Some(vec![parent.append_child(LayoutNode::new(*next_id, width as Coordinate, height as Coordinate))])
+        }
+        Edit::Resize { node, width, height } => {
+            let node = nodes[node % nodes.len()];
+            let target = unsafe { &mut *node.as_ptr() };
+            target.width = width as Coordinate;
+            target.height = height as Coordinate;
+            Some(vec![node])
+        }
+        Edit::Remove { node } => {
+            let node = unsafe { non_root(node)?.as_ref() };
+            let mut parent = node.parent?;
+            let id = node.id;
+            unsafe { parent.as_mut() }.remove_child(id);
+            Some(vec![parent])
+        }
+        Edit::Reparent { node, parent } => {
+            let node = non_root(node)?;
+            let new_parent = nodes[parent % nodes.len()];
+            if is_ancestor(node, new_parent) {
+                return None;
+            }
+            let mut old_parent = unsafe { node.as_ref() }.parent?;
+            let old_parent = unsafe { old_parent.as_mut() };
+            let index = old_parent.children.iter().position(|child| NonNull::from(&**child) == node)?;
+            let mut moved = old_parent.children.remove(index);
+            moved.parent = None;
+            let moved = unsafe { &mut *new_parent.as_ptr() }.append_child(*moved);
+            Some(vec![old_parent.into(), moved])
+        }
+    }
+}
+
+fn copy_subtree(node: &LayoutNode) -> LayoutNode {
+    LayoutNode::new_with_children(node.id, node.width, node.height, node.children.iter().map(|c| copy_subtree(c)).collect())
+}
+
+/// Copy of the tree laid out from scratch.
+pub fn full_layout(root: &LayoutNode, layout: &mut LayoutConfig) -> Box<LayoutNode> {
+    let mut copy = Box::new(LayoutNode::new(root.id, root.width, root.height));
+    for child in root.children.iter() {
+        copy.append_child(copy_subtree(child));
+    }
+    layout.layout(&mut copy);
+    copy
+}
@@ -0,0 +1,49 @@
+use super::*;
+use proptest::prelude::*;
+
+mod edits;
+pub use self::edits::{apply, build_tree, full_layout, Edit};
+
+fn edit() -> impl Strategy<Value = Edit> {
+    prop_oneof![
+        (any::<usize>(), 1..50u8, 1..50u8).prop_map(|(parent, width, height)| Edit::Insert { parent, width, height }),
+        (any::<usize>(), 1..50u8, 1..50u8).prop_map(|(node, width, height)| Edit::Resize { node, width, height }),
+        any::<usize>().prop_map(|node| Edit::Remove { node }),
+        (any::<usize>(), any::<usize>()).prop_map(|(node, parent)| Edit::Reparent { node, parent }),
+    ]
+}
+
+fn initial_tree() -> impl Strategy<Value = Vec<(usize, u8, u8)>> {
+    prop::collection::vec((any::<usize>(), 1..50u8, 1..50u8), 0..20)
+}
+
+fn positions(root: &LayoutNode) -> Vec<(usize, Coordinate, Coordinate)> {
+    root.iter().map(|node| (node.id, node.center.x, node.center.y)).collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn partial_layout_matches_layout(nodes in initial_tree(), edits in prop::collection::vec(edit(), 1..30)) {
+        let mut layout = LayoutConfig::new(10., 10.);
+        let mut tree = build_tree(&nodes);
+        let mut next_id = nodes.len();
+        layout.layout(&mut tree);
+        for (step, edit) in edits.iter().enumerate() {
+            let changed = match apply(&mut tree, edit, &mut next_id) {
+                Some(s) => s,
+                None => continue,
+            };
+            prop_assert_eq!(layout.try_partial_layout(&mut tree, &changed), Ok(()), "step {}: {:?}", step, edit);
+            let expected = full_layout(&tree, &mut layout);
+            for ((id, x, y), (_, full_x, full_y)) in positions(&tree).into_iter().zip(positions(&expected)) {
+                prop_assert!(
+                    (x - full_x).abs() < 1e-6 && (y - full_y).abs() < 1e-6,
+                    "step {}: {:?}, node {} at ({}, {}) instead of ({}, {})\n{}",
+                    step, edit, id, x, y, full_x, full_y, tree.str()
+                );
+            }
+        }
+    }
+}
@@ -5,5 +5,32 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 
+mod metrics;
+#[cfg(feature = "yggdrasil")]
+mod plot_diff;
+#[cfg(feature = "yggdrasil")]
+mod plot_dot;
+#[cfg(feature = "yggdrasil")]
+mod plot_failure;
+#[cfg(feature = "yggdrasil")]
+mod plot_html;
+mod plot_json;
+#[cfg(feature = "png")]
+mod plot_png;
 mod plot_svg;
-pub use crate::plot_svg::SvgPlotter;
+#[cfg(feature = "yggdrasil")]
+mod plot_text;
+pub use crate::{
+    metrics::TextMetrics,
+    plot_json::{NodeRect, PositionedEdge, PositionedNode, PositionedTree},
+    plot_svg::{FontEmbedding, LabelFormat, LineIndex, NodeLabel, NodeShape, NodeSpan, NodeStyle, SvgPlotter, Theme},
+};
+#[cfg(feature = "yggdrasil")]
+pub use crate::{
+    plot_failure::ParseFailure,
+    plot_svg::Focus,
+    plot_text::{Overflow, TextPlotter},
+};
+
+#[cfg(feature = "png")]
+pub use crate::plot_png::{PngError, PngOptions};
@@ -0,0 +1,330 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, ValueEnum};
+use yggdrasil_parser::{bootstrap::BootstrapRule, BootstrapParser};
+use yggdrasil_rt::YggdrasilParser;
+use yggdrasil_viewer::{Focus, FontEmbedding, LabelFormat, ParseFailure, SvgPlotter, TextMetrics, Theme};
+
+/// Draw the concrete syntax tree of a file parsed with the bootstrap grammar of yggdrasil.
+#[derive(Debug, Parser)]
+#[command(name = "ygg-viewer", version)]
+struct Arguments {
+    /// Input file, read from stdin if missing or `-`
+    input: Option<PathBuf>,
+    /// Output file, written to stdout if missing or `-`
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Output format, guessed from the extension of the output file, svg by default
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+    /// Rule the input is parsed as
+    #[arg(short, long, default_value = "Root")]
+    rule: String,
+    /// Colors of the nodes
+    #[arg(long, value_enum, default_value_t = ThemeName::Light)]
+    theme: ThemeName,
+    /// Vertical space between a parent and its children
+    #[arg(long)]
+    margin: Option<f64>,
+    /// Horizontal space between siblings
+    #[arg(long)]
+    peer_margin: Option<f64>,
+    /// Pack subtrees as tight as possible instead of one row per depth
+    #[arg(long)]
+    tidy: bool,
+    /// Horizontal space between a label and the border of its node
+    #[arg(long)]
+    padding_x: Option<f64>,
+    /// Vertical space between a label and the border of its node
+    #[arg(long)]
+    padding_y: Option<f64>,
+    /// Font size of the labels in pixels
+    #[arg(long)]
+    font_size: Option<f64>,
+    /// TrueType or OpenType font used to measure the labels
+    #[arg(long)]
+    font: Option<PathBuf>,
+    /// Embed the font in the svg or draw the labels as outlines, so that they look the same everywhere
+    #[arg(long, value_enum, default_value_t = EmbedFont::System)]
+    embed_font: EmbedFont,
+    /// Radius of the rounded corners of a node
+    #[arg(long)]
+    corner_radius: Option<f64>,
+    /// Minimum width of a node
+    #[arg(long)]
+    min_width: Option<f64>,
+    /// Stylesheet replacing the default one
+    #[arg(long)]
+    style: Option<PathBuf>,
+    /// Maximum number of characters in the label of a token
+    #[arg(long)]
+    max_chars: Option<usize>,
+    /// Escape whitespace in tokens instead of showing it with markers
+    #[arg(long)]
+    escape_whitespace: bool,
+    /// Draw chains of rules with a single child as one node
+    #[arg(long)]
+    compress_chains: bool,
+    /// Do not draw nodes of this rule, their children take their place
+    #[arg(long = "hide", value_name = "RULE")]
+    hidden_rules: Vec<String>,
+    /// Draw ignored tokens such as whitespace and comments
+    #[arg(long)]
+    trivia: bool,
+    /// Draw only the deepest node covering `START..END` in bytes or `LINE:COLUMN-LINE:COLUMN`
+    #[arg(long, value_name = "RANGE")]
+    focus: Option<String>,
+    /// Number of ancestors drawn above the focused node
+    #[arg(long, default_value_t = 3)]
+    focus_above: usize,
+    /// Number of levels drawn below the focused node
+    #[arg(long, default_value_t = 3)]
+    focus_below: usize,
+    /// Prefix of the ids in the svg, to put several trees in one page
+    #[arg(long, value_name = "PREFIX")]
+    id_prefix: Option<String>,
+    /// Draw the failure when the input does not parse, in svg or html
+    #[arg(long)]
+    show_failure: bool,
+    /// Pin the nodes of a dot graph at our layout, for `neato -n`
+    #[arg(long)]
+    positions: bool,
+    /// Resolution of a png image
+    #[arg(long, default_value_t = 96.0)]
+    dpi: f32,
+    /// Background color of a png image, the background of the theme by default
+    #[arg(long)]
+    background: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Svg,
+    /// interactive html page
+    Html,
+    /// interactive html page with the source text
+    Playground,
+    Png,
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ThemeName {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum EmbedFont {
+    /// fonts installed where the svg is shown
+    System,
+    /// glyphs of the labels as a base64 `@font-face`
+    Subset,
+    /// labels drawn as paths
+    Outlines,
+}
+
+/// List the rules the input can be parsed as, the match fails to compile when the grammar gains a rule missing here
+macro_rules! start_rules {
+    ($($rule:ident),* $(,)?; $($label:ident),* $(,)?) => {
+        /// Rules of the bootstrap grammar, all but the labels of literals, which only tag tokens
+        const RULES: &[BootstrapRule] = &[$(BootstrapRule::$rule),*];
+
+        #[allow(dead_code)]
+        fn all_rules(rule: BootstrapRule) {
+            match rule {
+                $(BootstrapRule::$rule)|* => {}
+                $(BootstrapRule::$label)|* => {}
+            }
+        }
+    };
+}
+
+start_rules! {
+    Root, Statement, GrammarStatement, GrammarBlock, ClassStatement, ClassBlock, OP_REMARK, UnionStatement, UnionBlock,
+    UnionBranch, BranchTag, RightAssociativity, GroupStatement, GroupBlock, GroupPair, ExternalStatement, LinkerBlock,
+    LinkerPair, KW_EXTERNAL, DecoratorCall, DecoratorName, FunctionCall, FunctionName, CallBody, Expression,
+    ExpressionHard, ExpressionSoft, ExpressionTag, Term, Prefix, Suffix, Atomic, GroupExpression, StringRaw,
+    StringRawText, StringNormal, StringItem, EscapedUnicode, EscapedCharacter, HEX, TextAny, RegexEmbed, RegexInner,
+    RegexRange, RegexNegative, Category, NamepathFree, Namepath, Identifier, Boolean, Integer, RangeExact, Range,
+    ModifierCall, OP_CATEGORY, KW_GRAMMAR, KW_IMPORT, KW_CLASS, KW_UNION, KW_GROUP, KW_CLIMB, KW_MACRO, WhiteSpace,
+    Comment,
+    ;
+    IgnoreText, IgnoreRegex,
+}
+
+fn main() -> ExitCode {
+    match run(Arguments::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Arguments) -> Result<(), String> {
+    let rule = RULES.iter().find(|rule| format!("{:?}", rule).eq_ignore_ascii_case(&args.rule)).copied().ok_or_else(|| {
+        let names: Vec<_> = RULES.iter().map(|rule| format!("{:?}", rule)).collect();
+        format!("unknown rule `{}`, expected one of {}", args.rule, names.join(", "))
+    })?;
+    let format = match (args.format, &args.output) {
+        (Some(format), _) => format,
+        (None, Some(path)) => guess_format(path)?,
+        (None, None) => Format::Svg,
+    };
+    let plotter = plotter(&args)?;
+    let (name, source) = read_input(args.input.as_deref())?;
+    let cst = match BootstrapParser::parse_cst(&source, rule) {
+        Ok(cst) => cst,
+        Err(e) => {
+            let message = format!("{}: failed to parse as {:?}\n{}", name, rule, e);
+            if args.show_failure {
+                let failure = ParseFailure::from_error(&source, &e);
+                let bytes = match format {
+                    Format::Svg => plotter.draw_failure(&failure).to_string().into_bytes(),
+                    Format::Html | Format::Playground => plotter.draw_failure_html(&failure).into_bytes(),
+                    _ => return Err(format!("{}\ncan not draw the failure as {:?}, use svg or html", message, format)),
+                };
+                write_output(args.output.as_deref(), bytes)?;
+            }
+            return Err(message);
+        }
+    };
+    let bytes = match format {
+        Format::Svg => plotter.draw(cst).to_string().into_bytes(),
+        Format::Html => plotter.draw_html(cst).into_bytes(),
+        Format::Playground => plotter.draw_playground(&source, cst).into_bytes(),
+        Format::Dot => plotter.draw_dot(cst, args.positions).into_bytes(),
+        Format::Json => plotter.draw_positioned(cst).to_json().into_bytes(),
+        Format::Png => draw_png(&plotter, cst, &args)?,
+    };
+    write_output(args.output.as_deref(), bytes)
+}
+
+fn write_output(path: Option<&Path>, bytes: Vec<u8>) -> Result<(), String> {
+    match path {
+        Some(path) if path != Path::new("-") => fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e)),
+        _ => std::io::stdout().write_all(&bytes).map_err(|e| format!("stdout: {}", e)),
+    }
+}
+
+fn guess_format(path: &Path) -> Result<Format, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "svg" => Ok(Format::Svg),
+        "html" | "htm" => Ok(Format::Html),
+        "png" => Ok(Format::Png),
+        "dot" | "gv" => Ok(Format::Dot),
+        "json" => Ok(Format::Json),
+        _ => Err(format!("{}: can not guess the format, use --format", path.display())),
+    }
+}
+
+fn read_input(path: Option<&Path>) -> Result<(String, String), String> {
+    match path {
+        Some(path) if path != Path::new("-") => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok((path.display().to_string(), text))
+        }
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text).map_err(|e| format!("stdin: {}", e))?;
+            Ok(("<stdin>".to_string(), text))
+        }
+    }
+}
+
+fn plotter(args: &Arguments) -> Result<SvgPlotter, String> {
+    let theme = match args.theme {
+        ThemeName::Light => Theme::light(),
+        ThemeName::Dark => Theme::dark(),
+        ThemeName::HighContrast => Theme::high_contrast(),
+    };
+    let font_embedding = match args.embed_font {
+        EmbedFont::System => FontEmbedding::System,
+        EmbedFont::Subset => FontEmbedding::Subset,
+        EmbedFont::Outlines => FontEmbedding::Outlines,
+    };
+    let mut label_format = LabelFormat::default().with_visible_whitespace(!args.escape_whitespace);
+    if let Some(max_chars) = args.max_chars {
+        label_format = label_format.with_max_chars(max_chars);
+    }
+    let mut plotter = SvgPlotter::default()
+        .with_theme(theme)
+        .with_label_format(label_format)
+        .with_layered(!args.tidy)
+        .with_compress_chains(args.compress_chains)
+        .with_trivia(args.trivia)
+        .with_font_embedding(font_embedding);
+    if let Some(range) = &args.focus {
+        plotter = plotter.with_focus(parse_focus(range)?.with_above(args.focus_above).with_below(args.focus_below));
+    }
+    for rule in args.hidden_rules.iter() {
+        plotter = plotter.with_hidden_rule_name(rule.as_str());
+    }
+    if let Some(prefix) = &args.id_prefix {
+        plotter = plotter.with_id_prefix(prefix.as_str());
+    }
+    if let Some(path) = &args.font {
+        let font = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // the font lives until the end of the process
+        let metrics = TextMetrics::new(Box::leak(font.into_boxed_slice())).map_err(|e| format!("{}: {}", path.display(), e))?;
+        plotter = plotter.with_metrics(metrics);
+    }
+    if let Some(path) = &args.style {
+        let style = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        plotter = plotter.with_style(style);
+    }
+    let (margin, peer_margin) = plotter.margins();
+    let (padding_x, padding_y) = plotter.padding();
+    plotter = plotter
+        .with_margins(args.margin.unwrap_or(margin), args.peer_margin.unwrap_or(peer_margin))
+        .with_padding(args.padding_x.unwrap_or(padding_x), args.padding_y.unwrap_or(padding_y));
+    if let Some(font_size) = args.font_size {
+        plotter = plotter.with_font_size(font_size);
+    }
+    if let Some(corner_radius) = args.corner_radius {
+        plotter = plotter.with_corner_radius(corner_radius);
+    }
+    if let Some(min_width) = args.min_width {
+        plotter = plotter.with_min_width(min_width);
+    }
+    Ok(plotter)
+}
+
+/// `START..END` or `LINE:COLUMN-LINE:COLUMN`, a single offset or position focuses on one character
+fn parse_focus(range: &str) -> Result<Focus, String> {
+    let invalid = || format!("invalid focus `{}`, expected `START..END` or `LINE:COLUMN-LINE:COLUMN`", range);
+    let number = |text: &str| text.trim().parse::<usize>().map_err(|_| invalid());
+    let position = |text: &str| match text.split_once(':') {
+        Some((line, column)) => Ok((number(line)?, number(column)?)),
+        None => Err(invalid()),
+    };
+    if range.contains(':') {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        Ok(Focus::lines(position(start)?, position(end)?))
+    }
+    else {
+        let (start, end) = match range.split_once("..") {
+            Some((start, end)) => (number(start)?, number(end)?),
+            None => (number(range)?, number(range)?),
+        };
+        Ok(Focus::bytes(start, end))
+    }
+}
+
+fn draw_png(plotter: &SvgPlotter, cst: yggdrasil_rt::TokenTree<BootstrapRule>, args: &Arguments) -> Result<Vec<u8>, String> {
+    let mut options = yggdrasil_viewer::PngOptions::default().with_dpi(args.dpi);
+    if let Some(background) = &args.background {
+        options = options.with_background(background.as_str());
+    }
+    plotter.draw_png(cst, &options).map_err(|e| e.to_string())
+}
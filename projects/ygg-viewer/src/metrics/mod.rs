@@ -0,0 +1,164 @@
+use std::fmt::{Debug, Formatter, Write};
+
+use ttf_parser::{Face, FaceParsingError, OutlineBuilder};
+use unicode_width::UnicodeWidthChar;
+
+mod subset;
+
+/// DejaVu Sans, see `LICENSE-DejaVu.txt`
+pub(crate) static BUNDLED_FONT: &[u8] = include_bytes!("DejaVuSans.ttf");
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Measure text with the glyph advances of a font, no system font is required.
+///
+/// Characters without a glyph fall back to their unicode width, one column is half an em.
+#[derive(Clone)]
+pub struct TextMetrics {
+    face: Face<'static>,
+    font_size: f64,
+}
+
+impl Debug for TextMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextMetrics").field("font_size", &self.font_size).finish()
+    }
+}
+
+impl Default for TextMetrics {
+    fn default() -> Self {
+        Self::new(BUNDLED_FONT).expect("the bundled font is valid")
+    }
+}
+
+impl TextMetrics {
+    /// Measure with a TrueType or OpenType font, the font size defaults to 16.
+    pub fn new(font: &'static [u8]) -> Result<Self, FaceParsingError> {
+        Ok(Self { face: Face::parse(font, 0)?, font_size: 16.0 })
+    }
+    /// Set the font size in pixels.
+    pub fn with_font_size(self, font_size: f64) -> Self {
+        Self { font_size, ..self }
+    }
+    /// The font size in pixels.
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+    /// The font file used for measurement.
+    pub(crate) fn font_data(&self) -> &'static [u8] {
+        self.face.raw_face().data
+    }
+    /// Distance between the baselines of two lines.
+    pub fn line_height(&self) -> f64 {
+        let height = self.face.ascender() as f64 - self.face.descender() as f64 + self.face.line_gap() as f64;
+        height * self.scale()
+    }
+    /// Width of a single line, control characters take no space.
+    pub fn line_width(&self, line: &str) -> f64 {
+        let mut width = 0.0;
+        let mut joined = false;
+        for c in line.chars() {
+            // the joined character is part of the previous glyph
+            if !std::mem::replace(&mut joined, c == ZERO_WIDTH_JOINER) {
+                width += self.advance(c);
+            }
+        }
+        width
+    }
+    /// Width of the widest line and height of all lines, `\r\n` and `\n` break lines.
+    pub fn text_size(&self, text: &str) -> (f64, f64) {
+        let mut width: f64 = 0.0;
+        let mut lines = 0;
+        for line in text.lines() {
+            width = width.max(self.line_width(line));
+            lines += 1;
+        }
+        (width, lines.max(1) as f64 * self.line_height())
+    }
+
+    /// The font with only the glyphs of these characters, the whole font if it can not be subset.
+    pub(crate) fn subset(&self, chars: impl IntoIterator<Item = char>) -> Vec<u8> {
+        subset::subset(self.font_data(), chars).unwrap_or_else(|| self.font_data().to_vec())
+    }
+    /// Distance from the alphabetic baseline up to the middle of lowercase letters, as `dominant-baseline: middle`.
+    pub(crate) fn middle(&self) -> f64 {
+        let x_height = self.face.x_height().unwrap_or(self.face.ascender() / 2);
+        x_height as f64 * self.scale() / 2.0
+    }
+    /// Path data of the glyphs of a line starting at `x` on the baseline `y`, characters without a glyph are skipped.
+    pub(crate) fn outline(&self, line: &str, x: f64, y: f64) -> String {
+        let mut path = PathData { data: String::new(), x, y, scale: self.scale() };
+        let mut joined = false;
+        for c in line.chars() {
+            if std::mem::replace(&mut joined, c == ZERO_WIDTH_JOINER) {
+                continue;
+            }
+            if let Some(glyph) = self.face.glyph_index(c) {
+                self.face.outline_glyph(glyph, &mut path);
+            }
+            path.x += self.advance(c);
+        }
+        path.data
+    }
+
+    fn advance(&self, c: char) -> f64 {
+        let columns = match c.width() {
+            Some(columns) => columns,
+            None => return 0.0,
+        };
+        match self.face.glyph_index(c).and_then(|glyph| self.face.glyph_hor_advance(glyph)) {
+            Some(advance) => advance as f64 * self.scale(),
+            None => columns as f64 * self.font_size / 2.0,
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        self.font_size / self.face.units_per_em() as f64
+    }
+}
+
+/// Svg path data of glyphs scaled to pixels, the y axis of fonts points up
+struct PathData {
+    data: String,
+    x: f64,
+    y: f64,
+    scale: f64,
+}
+
+impl PathData {
+    fn push(&mut self, command: char, points: &[(f32, f32)]) {
+        self.data.push(command);
+        for (i, (x, y)) in points.iter().enumerate() {
+            let separator = if i == 0 { "" } else { " " };
+            let (x, y) = (self.x + *x as f64 * self.scale, self.y - *y as f64 * self.scale);
+            write!(self.data, "{}{} {}", separator, round(x), round(y)).ok();
+        }
+    }
+}
+
+/// Two decimals are below the resolution of any screen
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0 + 0.0
+}
+
+impl OutlineBuilder for PathData {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.push('M', &[(x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push('L', &[(x, y)]);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.push('Q', &[(x1, y1), (x, y)]);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.push('C', &[(x1, y1), (x2, y2), (x, y)]);
+    }
+
+    fn close(&mut self) {
+        self.data.push('Z');
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ttf_parser::{Face, GlyphId, Tag};
+
+/// Tables kept in a subset, layout and kerning tables are dropped since labels are not shaped
+const KEPT_TABLES: &[&[u8; 4]] = &[
+    b"OS/2", b"cmap", b"cvt ", b"fpgm", b"gasp", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"name", b"post",
+    b"prep",
+];
+
+/// A TrueType font with only the glyphs of these characters and the glyphs they are composed of.
+///
+/// Returns `None` if the font has no `glyf` table, such as a font with CFF outlines.
+pub(crate) fn subset(font: &[u8], chars: impl IntoIterator<Item = char>) -> Option<Vec<u8>> {
+    let face = Face::parse(font, 0).ok()?;
+    let raw = face.raw_face();
+    let table = |tag: &[u8; 4]| raw.table(Tag::from_bytes(tag));
+    let (head, glyf, loca, hhea, hmtx) = (table(b"head")?, table(b"glyf")?, table(b"loca")?, table(b"hhea")?, table(b"hmtx")?);
+    let long_loca = read_u16(head, 50)? == 1;
+    let glyph = |id: u16| -> Option<&[u8]> {
+        let (start, end) = match long_loca {
+            true => (read_u32(loca, 4 * id as usize)? as usize, read_u32(loca, 4 * id as usize + 4)? as usize),
+            false => (2 * read_u16(loca, 2 * id as usize)? as usize, 2 * read_u16(loca, 2 * id as usize + 2)? as usize),
+        };
+        glyf.get(start..end)
+    };
+
+    let mut mapping = BTreeMap::new();
+    for c in chars {
+        if let Some(GlyphId(id)) = face.glyph_index(c) {
+            mapping.insert(c as u32, id);
+        }
+    }
+    // the components of composite glyphs are needed too
+    let mut used = BTreeSet::new();
+    let mut stack: Vec<u16> = mapping.values().copied().chain([0]).collect();
+    while let Some(id) = stack.pop() {
+        if used.insert(id) {
+            stack.extend(components(glyph(id)?).into_iter().map(|(_, component)| component));
+        }
+    }
+    // the kept glyphs are numbered in their order in the font
+    let renumber: BTreeMap<u16, u16> = used.iter().enumerate().map(|(new, old)| (*old, new as u16)).collect();
+    let count = renumber.len() as u16;
+    mapping.values_mut().for_each(|id| *id = renumber[id]);
+
+    let metrics = read_u16(hhea, 34)? as usize;
+    let mut new_glyf = vec![];
+    let mut new_hmtx = vec![];
+    let mut offsets = vec![0];
+    for &id in &used {
+        let mut data = glyph(id)?.to_vec();
+        for (offset, component) in components(&data) {
+            data[offset..offset + 2].copy_from_slice(&renumber[&component].to_be_bytes());
+        }
+        new_glyf.extend_from_slice(&data);
+        pad(&mut new_glyf);
+        offsets.push(new_glyf.len());
+        // glyphs after the last long metric share its advance and only have a left side bearing
+        let advance = 4 * (id as usize).min(metrics.checked_sub(1)?);
+        let bearing = match (id as usize).checked_sub(metrics) {
+            None => 4 * id as usize + 2,
+            Some(index) => 4 * metrics + 2 * index,
+        };
+        new_hmtx.extend_from_slice(hmtx.get(advance..advance + 2)?);
+        new_hmtx.extend_from_slice(hmtx.get(bearing..bearing + 2)?);
+    }
+    let long_loca = long_loca || new_glyf.len() > 0x1FFFE;
+    let mut new_loca = vec![];
+    for offset in offsets {
+        match long_loca {
+            true => push_u32(&mut new_loca, offset as u32),
+            false => push_u16(&mut new_loca, (offset / 2) as u16),
+        }
+    }
+
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = vec![];
+    for record in raw.table_records {
+        let tag = record.tag.to_bytes();
+        if !KEPT_TABLES.contains(&&tag) {
+            continue;
+        }
+        let mut data = table(&tag)?.to_vec();
+        match &tag {
+            b"head" => {
+                data[8..12].copy_from_slice(&[0; 4]);
+                data[50..52].copy_from_slice(&(long_loca as u16).to_be_bytes());
+            }
+            b"hhea" => data[34..36].copy_from_slice(&count.to_be_bytes()),
+            b"maxp" => data[4..6].copy_from_slice(&count.to_be_bytes()),
+            b"glyf" => data = new_glyf.clone(),
+            b"loca" => data = new_loca.clone(),
+            b"hmtx" => data = new_hmtx.clone(),
+            b"cmap" => data = cmap(&mapping),
+            // version 3 has no glyph names
+            b"post" => {
+                data.truncate(32);
+                data[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+            }
+            _ => {}
+        }
+        tables.push((tag, data));
+    }
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let mut out = font.get(0..4)?.to_vec();
+    let (search_range, entry_selector) = binary_search(tables.len() as u16, 16);
+    push_u16(&mut out, tables.len() as u16);
+    push_u16(&mut out, search_range);
+    push_u16(&mut out, entry_selector);
+    push_u16(&mut out, tables.len() as u16 * 16 - search_range);
+    let mut offset = out.len() + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        out.extend_from_slice(tag);
+        push_u32(&mut out, checksum(data));
+        push_u32(&mut out, offset as u32);
+        push_u32(&mut out, data.len() as u32);
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in &tables {
+        out.extend_from_slice(data);
+        pad(&mut out);
+    }
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+        out[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    Some(out)
+}
+
+/// The glyphs a composite glyph is made of, with the offsets of their ids in the glyph data
+fn components(glyph: &[u8]) -> Vec<(usize, u16)> {
+    const ARGS_ARE_WORDS: u16 = 0x0001;
+    const HAS_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const HAS_X_Y_SCALE: u16 = 0x0040;
+    const HAS_2_BY_2: u16 = 0x0080;
+    let mut out = vec![];
+    if read_u16(glyph, 0).is_none_or(|contours| contours as i16 >= 0) {
+        return out;
+    }
+    let mut offset = 10;
+    while let (Some(flags), Some(id)) = (read_u16(glyph, offset), read_u16(glyph, offset + 2)) {
+        out.push((offset + 2, id));
+        offset += 4 + if flags & ARGS_ARE_WORDS != 0 { 4 } else { 2 };
+        offset += match flags {
+            _ if flags & HAS_SCALE != 0 => 2,
+            _ if flags & HAS_X_Y_SCALE != 0 => 4,
+            _ if flags & HAS_2_BY_2 != 0 => 8,
+            _ => 0,
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    out
+}
+
+/// A cmap with a format 4 subtable for the basic plane, and a format 12 subtable if other planes are used
+fn cmap(mapping: &BTreeMap<u32, u16>) -> Vec<u8> {
+    // ranges of consecutive characters with consecutive glyphs
+    let mut ranges: Vec<(u32, u32, u16)> = vec![];
+    for (&c, &id) in mapping {
+        match ranges.last_mut() {
+            Some((start, end, first)) if *end + 1 == c && (*first as u32 + c - *start) == id as u32 => *end = c,
+            _ => ranges.push((c, c, id)),
+        }
+    }
+    let mut basic: Vec<(u32, u32, u16)> = ranges.iter().copied().filter(|(_, end, _)| *end < 0xFFFF).collect();
+    basic.push((0xFFFF, 0xFFFF, 0));
+    let segments = basic.len() as u16;
+    let (search_range, entry_selector) = binary_search(segments, 2);
+    let mut format4 = vec![];
+    for value in [4, 16 + 8 * segments, 0, 2 * segments, search_range, entry_selector, 2 * segments - search_range] {
+        push_u16(&mut format4, value);
+    }
+    basic.iter().for_each(|(_, end, _)| push_u16(&mut format4, *end as u16));
+    push_u16(&mut format4, 0);
+    basic.iter().for_each(|(start, _, _)| push_u16(&mut format4, *start as u16));
+    basic.iter().for_each(|(start, _, id)| push_u16(&mut format4, (*id as u32).wrapping_sub(*start) as u16));
+    basic.iter().for_each(|_| push_u16(&mut format4, 0));
+
+    let mut subtables = vec![(3, 1, format4)];
+    if ranges.iter().any(|(_, end, _)| *end >= 0xFFFF) {
+        let mut format12 = vec![];
+        push_u16(&mut format12, 12);
+        push_u16(&mut format12, 0);
+        push_u32(&mut format12, 16 + 12 * ranges.len() as u32);
+        push_u32(&mut format12, 0);
+        push_u32(&mut format12, ranges.len() as u32);
+        for (start, end, id) in &ranges {
+            push_u32(&mut format12, *start);
+            push_u32(&mut format12, *end);
+            push_u32(&mut format12, *id as u32);
+        }
+        subtables.push((3, 10, format12));
+    }
+    let mut out = vec![];
+    push_u16(&mut out, 0);
+    push_u16(&mut out, subtables.len() as u16);
+    let mut offset = 4 + 8 * subtables.len();
+    for (platform, encoding, data) in &subtables {
+        push_u16(&mut out, *platform);
+        push_u16(&mut out, *encoding);
+        push_u32(&mut out, offset as u32);
+        offset += data.len();
+    }
+    for (_, _, data) in subtables {
+        out.extend(data);
+    }
+    out
+}
+
+/// The search range and entry selector of a binary search header
+fn binary_search(count: u16, size: u16) -> (u16, u16) {
+    let power = 15 - count.max(1).leading_zeros() as u16;
+    (size << power, power)
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad(data: &mut Vec<u8>) {
+    data.resize((data.len() + 3) & !3, 0);
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
@@ -0,0 +1,22 @@
+(function () {
+    "use strict";
+    const svg = document.querySelector("#canvas svg");
+    const header = document.querySelector("header");
+    if (!svg || !header) {
+        return;
+    }
+    // show both trees, or hide the nodes missing from one side
+    const views = [["Both", ""], ["Old", "diff-old"], ["New", "diff-new"]];
+    for (const [name, view] of views) {
+        const button = document.createElement("button");
+        button.type = "button";
+        button.textContent = name;
+        button.addEventListener("click", function () {
+            svg.classList.remove("diff-old", "diff-new");
+            if (view) {
+                svg.classList.add(view);
+            }
+        });
+        header.insertBefore(button, header.lastElementChild);
+    }
+})();
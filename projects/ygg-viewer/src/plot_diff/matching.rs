@@ -0,0 +1,261 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::TreeInfo;
+
+use crate::{plot_svg::SvgTree, NodeLabel, NodeSpan, SvgPlotter};
+
+/// A drawn node of one side of the diff, ids are in pre-order
+#[derive(Clone, Debug)]
+pub(super) struct Flat {
+    /// rule names of the node, joined
+    pub rule: String,
+    /// innermost rule, picks the shape
+    pub style: String,
+    pub label: String,
+    pub text: String,
+    pub leaf: bool,
+    pub span: NodeSpan,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// hash of the rules and tokens of the subtree
+    hash: u64,
+    /// number of nodes in the subtree
+    size: usize,
+}
+
+/// The nodes of a tree as drawn by the plotter, the root comes first.
+pub(super) fn flatten<R>(plot: &SvgPlotter, tree: TokenTree<R>) -> Vec<Flat>
+where
+    R: YggdrasilRule,
+{
+    let svg = SvgTree::new(tree, plot);
+    let mut out: Vec<Flat> = vec![];
+    let mut stack: Vec<(_, Option<usize>)> = vec![(svg.root(), None)];
+    while let Some((node, parent)) = stack.pop() {
+        let id = out.len();
+        if let Some(parent) = parent {
+            out[parent].children.push(id);
+        }
+        out.push(Flat {
+            rule: node.rules().join(" > "),
+            style: format!("{:?}", node.get_rule()),
+            label: svg.label(&node),
+            text: node.get_string().to_string(),
+            leaf: !node.has_child(false),
+            span: NodeSpan::of(&node, &svg.lines),
+            parent,
+            children: vec![],
+            hash: 0,
+            size: 1,
+        });
+        let children: Vec<_> = svg.children(&node).collect();
+        stack.extend(children.into_iter().rev().map(|child| (child, Some(id))));
+    }
+    // children have larger ids than their parent
+    for id in (0..out.len()).rev() {
+        let mut hasher = DefaultHasher::new();
+        out[id].rule.hash(&mut hasher);
+        if out[id].leaf {
+            out[id].text.hash(&mut hasher);
+        }
+        let mut size = 1;
+        for child in out[id].children.clone() {
+            out[child].hash.hash(&mut hasher);
+            size += out[child].size;
+        }
+        out[id].hash = hasher.finish();
+        out[id].size = size;
+    }
+    out
+}
+
+/// Pairs of nodes that are the same node in the old and the new tree
+#[derive(Clone, Debug)]
+pub(super) struct Matching {
+    /// counterpart of every old node
+    pub old: Vec<Option<usize>>,
+    /// counterpart of every new node
+    pub new: Vec<Option<usize>>,
+}
+
+impl Matching {
+    fn link(&mut self, old: usize, new: usize) {
+        self.old[old] = Some(new);
+        self.new[new] = Some(old);
+    }
+    fn link_subtree(&mut self, old: &[Flat], new: &[Flat], o: usize, n: usize) {
+        let mut stack = vec![(o, n)];
+        while let Some((o, n)) = stack.pop() {
+            self.link(o, n);
+            stack.extend(old[o].children.iter().copied().zip(new[n].children.iter().copied()));
+        }
+    }
+    fn same_parent(&self, old: &[Flat], new: &[Flat], o: usize, n: usize) -> bool {
+        match (old[o].parent, new[n].parent) {
+            (Some(po), Some(pn)) => self.old[po] == Some(pn),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Match the nodes of two trees.
+///
+/// Identical subtrees are matched first, largest first. Inner nodes are then matched by the share of their matched
+/// descendants, and the remaining children of matched nodes by rule in order.
+pub(super) fn match_trees(old: &[Flat], new: &[Flat]) -> Matching {
+    let mut m = Matching { old: vec![None; old.len()], new: vec![None; new.len()] };
+    // single tokens are too ambiguous, they are matched under their parents
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (n, node) in new.iter().enumerate().filter(|(_, node)| node.size > 1) {
+        by_hash.entry(node.hash).or_default().push(n);
+    }
+    let mut order: Vec<usize> = (0..old.len()).filter(|&o| old[o].size > 1).collect();
+    order.sort_by_key(|&o| Reverse(old[o].size));
+    for o in order {
+        if m.old[o].is_some() {
+            continue;
+        }
+        let best = by_hash.get(&old[o].hash).and_then(|candidates| {
+            candidates
+                .iter()
+                .copied()
+                .filter(|&n| m.new[n].is_none())
+                .min_by_key(|&n| (!m.same_parent(old, new, o, n), old[o].span.start.abs_diff(new[n].span.start)))
+        });
+        if let Some(n) = best {
+            m.link_subtree(old, new, o, n);
+        }
+    }
+    // descendants come after their ancestors in pre-order
+    for o in (0..old.len()).rev() {
+        if m.old[o].is_some() || old[o].leaf {
+            continue;
+        }
+        let mut votes: HashMap<usize, usize> = HashMap::new();
+        for d in descendants(old, o) {
+            let mut ancestor = m.old[d].and_then(|n| new[n].parent);
+            while let Some(a) = ancestor {
+                if m.new[a].is_none() && new[a].rule == old[o].rule {
+                    *votes.entry(a).or_default() += 1;
+                }
+                ancestor = new[a].parent;
+            }
+        }
+        let dice = |n: usize, common: usize| 2.0 * common as f64 / (old[o].size + new[n].size - 2) as f64;
+        let best = votes.into_iter().map(|(n, common)| (n, dice(n, common))).filter(|(_, score)| *score >= 0.5).max_by(
+            |(a, x), (b, y)| {
+                x.total_cmp(y).then_with(|| {
+                    old[o].span.start.abs_diff(new[*b].span.start).cmp(&old[o].span.start.abs_diff(new[*a].span.start))
+                })
+            },
+        );
+        if let Some((n, _)) = best {
+            m.link(o, n);
+        }
+    }
+    if !old.is_empty() && !new.is_empty() && m.old[0].is_none() && m.new[0].is_none() {
+        m.link(0, 0);
+    }
+    // children of matched nodes with the same rule, keeping their order
+    let mut queue: Vec<usize> = (0..old.len()).filter(|&o| m.old[o].is_some()).collect();
+    let mut index = 0;
+    while let Some(&o) = queue.get(index) {
+        index += 1;
+        let n = m.old[o].expect("only matched nodes are queued");
+        let mut start = 0;
+        for &co in old[o].children.iter() {
+            if let Some(cn) = m.old[co] {
+                if let Some(position) = new[n].children.iter().position(|&c| c == cn) {
+                    start = start.max(position + 1);
+                }
+                continue;
+            }
+            let found = new[n]
+                .children
+                .iter()
+                .skip(start)
+                .position(|&cn| m.new[cn].is_none() && new[cn].style == old[co].style && new[cn].leaf == old[co].leaf);
+            if let Some(offset) = found {
+                let cn = new[n].children[start + offset];
+                m.link(co, cn);
+                queue.push(co);
+                start += offset + 1;
+            }
+        }
+    }
+    m
+}
+
+fn descendants(tree: &[Flat], id: usize) -> impl Iterator<Item = usize> {
+    // the subtree is a contiguous range in pre-order
+    id + 1..id + tree[id].size
+}
+
+/// Whether every new node moved to another parent or position, or changed its label
+pub(super) fn changes(old: &[Flat], new: &[Flat], m: &Matching) -> (Vec<bool>, Vec<bool>) {
+    let mut moved = vec![false; new.len()];
+    let mut relabeled = vec![false; new.len()];
+    for n in 0..new.len() {
+        let Some(o) = m.new[n]
+        else {
+            continue;
+        };
+        relabeled[n] = old[o].rule != new[n].rule || old[o].label != new[n].label;
+        moved[n] = !m.same_parent(old, new, o, n);
+    }
+    // siblings that kept their parent but not their order, the longest run in order stays
+    let mut index = vec![0; old.len()];
+    for node in old.iter() {
+        for (i, &c) in node.children.iter().enumerate() {
+            index[c] = i;
+        }
+    }
+    for (n, node) in new.iter().enumerate() {
+        if m.new[n].is_none() {
+            continue;
+        }
+        let siblings: Vec<(usize, usize)> =
+            node.children.iter().filter(|&&c| !moved[c]).filter_map(|&c| m.new[c].map(|oc| (c, index[oc]))).collect();
+        let positions: Vec<usize> = siblings.iter().map(|(_, p)| *p).collect();
+        let mut keep = vec![false; siblings.len()];
+        for i in longest_increasing(&positions) {
+            keep[i] = true;
+        }
+        for ((c, _), keep) in siblings.iter().zip(keep) {
+            moved[*c] |= !keep;
+        }
+    }
+    (moved, relabeled)
+}
+
+/// Indices of a longest strictly increasing subsequence
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // tails[k] is the index of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let k = tails.partition_point(|&t| values[t] < *value);
+        previous[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i)
+        }
+        else {
+            tails[k] = i
+        }
+    }
+    let mut out = vec![];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        out.push(i);
+        current = previous[i];
+    }
+    out.reverse();
+    out
+}
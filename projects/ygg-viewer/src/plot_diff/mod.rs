@@ -0,0 +1,235 @@
+use std::collections::{BTreeSet, HashMap};
+
+use shape_svg::ToSVG;
+use svg::{
+    node::element::{Group, Style, Title, SVG},
+    Document, Node,
+};
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::{Coordinate, LayoutConfig, Rectangle, TreeArena, TreeInfo};
+
+use crate::{
+    plot_svg::{aria_label, escape_xml, shape},
+    SvgPlotter,
+};
+
+use self::matching::{changes, flatten, match_trees, Flat, Matching};
+
+mod matching;
+
+/// A node of the merged tree, from the new tree or removed from the old one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Old(usize),
+    New(usize),
+}
+
+/// The matched trees, drawn as the new tree with the removed nodes put back
+#[derive(Debug)]
+struct Diff {
+    old: Vec<Flat>,
+    new: Vec<Flat>,
+    matching: Matching,
+    moved: Vec<bool>,
+    relabeled: Vec<bool>,
+}
+
+impl Diff {
+    fn flat(&self, side: Side) -> &Flat {
+        match side {
+            Side::Old(o) => &self.old[o],
+            Side::New(n) => &self.new[n],
+        }
+    }
+    /// The old node of a relabeled node
+    fn old_label(&self, side: Side) -> Option<&str> {
+        match side {
+            Side::New(n) if self.relabeled[n] => self.matching.new[n].map(|o| self.old[o].label.as_str()),
+            _ => None,
+        }
+    }
+    /// Css classes of the changes of a node
+    fn classes(&self, side: Side) -> String {
+        match side {
+            Side::Old(_) => "diff-removed".to_string(),
+            Side::New(n) if self.matching.new[n].is_none() => "diff-added".to_string(),
+            Side::New(n) => {
+                let mut classes = vec![];
+                if self.moved[n] {
+                    classes.push("diff-moved")
+                }
+                if self.relabeled[n] {
+                    classes.push("diff-relabeled")
+                }
+                if classes.is_empty() {
+                    classes.push("diff-same")
+                }
+                classes.join(" ")
+            }
+        }
+    }
+    /// Children of the merged tree, removed nodes follow the counterpart of their previous sibling
+    fn children(&self, side: Side) -> Vec<Side> {
+        match side {
+            // matched children of a removed node are drawn at their new place
+            Side::Old(o) => {
+                self.old[o].children.iter().filter(|&&c| self.matching.old[c].is_none()).map(|&c| Side::Old(c)).collect()
+            }
+            Side::New(n) => {
+                let mut out: Vec<Side> = self.new[n].children.iter().map(|&c| Side::New(c)).collect();
+                let Some(o) = self.matching.new[n]
+                else {
+                    return out;
+                };
+                let mut last = None;
+                for &c in self.old[o].children.iter() {
+                    match self.matching.old[c] {
+                        None => {
+                            let index = last.map_or(0, |i| i + 1);
+                            out.insert(index, Side::Old(c));
+                            last = Some(index);
+                        }
+                        Some(cn) => {
+                            if let Some(index) = out.iter().position(|&s| s == Side::New(cn)) {
+                                last = Some(index);
+                            }
+                        }
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DiffTree<'p> {
+    diff: &'p Diff,
+    plot: &'p SvgPlotter,
+}
+
+impl<'p> TreeInfo for DiffTree<'p> {
+    type Node = Side;
+
+    fn root(&self) -> Self::Node {
+        Side::New(0)
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        self.diff.children(*node).into_iter()
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        let (width, _) = self.size(*node);
+        (width + 2.0 * self.plot.padding.0).max(self.plot.min_width)
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        let (_, height) = self.size(*node);
+        height + 2.0 * self.plot.padding.1
+    }
+}
+
+impl<'p> DiffTree<'p> {
+    /// Large enough for the old and the new label
+    fn size(&self, side: Side) -> (Coordinate, Coordinate) {
+        let metrics = &self.plot.metrics;
+        let (width, height) = metrics.text_size(&self.diff.flat(side).label);
+        match self.diff.old_label(side) {
+            Some(old) => {
+                let (old_width, old_height) = metrics.text_size(old);
+                (width.max(old_width), height.max(old_height))
+            }
+            None => (width, height),
+        }
+    }
+}
+
+impl SvgPlotter {
+    /// Draw the changes from the old to the new tree.
+    ///
+    /// Nodes are matched by rule, text and position. The removed nodes are drawn into the new tree, added, removed,
+    /// moved and relabeled nodes get the classes `diff-added`, `diff-removed`, `diff-moved` and `diff-relabeled`.
+    pub fn draw_diff<R>(&self, old: TokenTree<R>, new: TokenTree<R>) -> SVG
+    where
+        R: YggdrasilRule,
+    {
+        let old = flatten(self, old);
+        let new = flatten(self, new);
+        let matching = match_trees(&old, &new);
+        let (moved, relabeled) = changes(&old, &new, &matching);
+        let diff = Diff { old, new, matching, moved, relabeled };
+        self.write_diff(&diff)
+    }
+    /// Draw the diff in the html viewer of [`SvgPlotter::draw_html`], with buttons to show the old or the new tree.
+    pub fn draw_diff_html<R>(&self, old: TokenTree<R>, new: TokenTree<R>) -> String
+    where
+        R: YggdrasilRule,
+    {
+        self.page(&self.draw_diff(old, new).to_string(), "", include_str!("diff.js"))
+    }
+
+    /// The edges and a tree of node groups as in [`SvgPlotter::draw`], titled with a count of the changes
+    fn write_diff(&self, diff: &Diff) -> SVG {
+        let layout = LayoutConfig::new(self.margin, self.peer_margin).with_layered(self.layered);
+        let root = TreeArena::build(DiffTree { diff, plot: self }, &layout);
+        let summary =
+            format!("Changes to the syntax tree of {}", diff.new.first().map_or("nothing", |flat| flat.rule.as_str()));
+        let mut bbox: Option<Rectangle> = None;
+        let mut edges = Group::new().set("class", "cst-edges").set("aria-hidden", "true");
+        let mut items = Group::new().set("role", "tree").set("aria-label", escape_xml(&summary));
+        let mut shapes = HashMap::new();
+        let mut glyphs = BTreeSet::new();
+        let mut counts: Vec<(&str, usize)> = ["nodes", "added", "removed", "moved", "relabeled"].map(|name| (name, 0)).to_vec();
+        for (node, side) in root.into_iter() {
+            let area = node.boundary();
+            *bbox.get_or_insert(area) |= area;
+            let flat = diff.flat(*side);
+            let classes = diff.classes(*side);
+            counts[0].1 += 1;
+            for (name, count) in counts.iter_mut().skip(1) {
+                *count += classes.split(' ').any(|class| class.strip_prefix("diff-") == Some(*name)) as usize;
+            }
+            if let Some(line) = root.get_link(node) {
+                edges = edges.add(line.to_svg().set("class", escape_xml(&classes)).set("data-child", node.id));
+            }
+            let style = shapes.entry(flat.style.as_str()).or_insert_with(|| self.theme.style_of(&flat.style, flat.leaf)).shape;
+            let kind = if flat.leaf { "leaf" } else { "node" };
+            let mut title = flat.text.clone();
+            if let Some(old) = diff.old_label(*side) {
+                title = format!("{}\nwas: {}", title, old);
+            }
+            let changes = classes.split(' ').map(|class| class.trim_start_matches("diff-")).collect::<Vec<_>>().join(", ");
+            let label = format!("{} ({})", aria_label(&flat.rule, &flat.text), changes);
+            let group = self
+                .tree_item(node, &format!("cst-node {}", escape_xml(&classes)), &label)
+                .set("data-side", if let Side::Old(_) = side { "old" } else { "new" });
+            let mut text =
+                |class: &str, label: &str| self.write_label(&format!("{} {}", kind, class), label, area.center(), &mut glyphs);
+            let mut group = group
+                .set("data-rule", escape_xml(&flat.rule))
+                .set("data-start", flat.span.start)
+                .set("data-end", flat.span.end)
+                .set("data-range", flat.span.range())
+                .add(Title::new().add(svg::node::Text::new(escape_xml(&title))))
+                .add(shape(area, style, self.corner_radius, kind))
+                .add(text("label-new", &flat.label));
+            if let Some(old) = diff.old_label(*side) {
+                group = group.add(text("label-old", old));
+            }
+            items = items.add(group);
+        }
+        let body: Vec<Box<dyn Node>> = vec![Box::new(edges), Box::new(items)];
+        let bbox = bbox.unwrap_or_else(Rectangle::empty);
+        let css = format!("{}\n{}\n{}{}", self.style, self.theme.base_css(), include_str!("diff.css"), self.font_css(&glyphs));
+        let description = counts.iter().map(|(name, count)| format!("{} {}", count, name)).collect::<Vec<_>>().join(", ");
+        let mut document = self.titled(Document::new(), &summary, &description).add(Style::new(css));
+        for node in body {
+            document = document.add(node);
+        }
+        document
+            .set("font-size", self.metrics.font_size())
+            .set("viewBox", (bbox.min.x, bbox.min.y, bbox.width(), bbox.height()))
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::{Coordinate, Rectangle};
+
+use crate::{
+    plot_svg::{escape_control, SvgTree},
+    NodeLabel, NodeShape, NodeStyle, SvgPlotter,
+};
+
+/// Graphviz points per pixel, 72 points per inch at 96 pixels per inch
+const POINTS: Coordinate = 0.75;
+
+impl SvgPlotter {
+    /// Draw a graphviz dot graph with the labels and colors of the svg.
+    ///
+    /// With `positions`, every node is pinned at its place in our layout, render with `neato -n` to keep it.
+    pub fn draw_dot<R>(&self, tree: TokenTree<R>, positions: bool) -> String
+    where
+        R: YggdrasilRule,
+    {
+        let tree = SvgTree::new(tree, self);
+        let root = tree.arena();
+        let mut bbox: Option<Rectangle> = None;
+        for (node, _) in root.into_iter() {
+            *bbox.get_or_insert(node.boundary()) |= node.boundary();
+        }
+        let bbox = bbox.unwrap_or_else(Rectangle::empty);
+        let theme = &self.theme;
+        let mut out = String::from("digraph cst {\n");
+        match &theme.background {
+            Some(color) => writeln!(out, "    graph [ordering=out, bgcolor={}];", quote(color)),
+            None => writeln!(out, "    graph [ordering=out];"),
+        }
+        .unwrap();
+        writeln!(
+            out,
+            "    node [fontname=\"DejaVu Sans\", fontsize={}, margin=\"0.08,0.02\"];",
+            self.metrics.font_size() * POINTS
+        )
+        .unwrap();
+        writeln!(out, "    edge [color={}, arrowhead=none];", quote(&theme.edge)).unwrap();
+        for (node, pair) in root.into_iter() {
+            let is_leaf = !pair.has_child(false);
+            let rule = format!("{:?}", pair.get_rule());
+            let style = theme.style_of(&rule, is_leaf);
+            write!(
+                out,
+                "    n{} [label={}, tooltip={}, {}",
+                node.id,
+                quote(&tree.label(pair)),
+                quote(&escape_control(&pair.get_string())),
+                attributes(&style)
+            )
+            .unwrap();
+            if positions {
+                // graphviz puts the origin at the bottom
+                let x = (node.center.x - bbox.min.x) * POINTS;
+                let y = (bbox.max.y - node.center.y) * POINTS;
+                write!(
+                    out,
+                    ", pos=\"{:.2},{:.2}!\", width={:.4}, height={:.4}, fixedsize=true",
+                    x,
+                    y,
+                    node.width * POINTS / 72.0,
+                    node.height * POINTS / 72.0
+                )
+                .unwrap();
+            }
+            out.push_str("];\n");
+        }
+        // edges in child order, kept by `ordering=out`
+        for (node, _) in root.into_iter() {
+            for child in node.children.iter() {
+                writeln!(out, "    n{} -> n{};", node.id, child.id).unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Shape and color attributes of a node
+fn attributes(style: &NodeStyle) -> String {
+    let shape = match style.shape {
+        NodeShape::Rounded | NodeShape::Pill => "shape=box, style=\"filled,rounded\"",
+        NodeShape::Rectangle => "shape=box, style=filled",
+        NodeShape::Ellipse => "shape=ellipse, style=filled",
+    };
+    let border = match style.stroke.as_str() {
+        "none" => "penwidth=0".to_string(),
+        stroke => format!("color={}", quote(stroke)),
+    };
+    format!("{}, fillcolor={}, {}, fontcolor={}", shape, quote(&style.fill), border, quote(&style.text))
+}
+
+/// A double quoted dot string, lines are centered
+fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
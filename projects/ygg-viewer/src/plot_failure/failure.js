@@ -0,0 +1,29 @@
+(function () {
+    "use strict";
+    const svg = document.querySelector("#canvas svg");
+    const source = document.getElementById("source");
+    if (!svg || !source) {
+        return;
+    }
+    // split the source pane at the failure, the pane is cut at both ends of the failing text
+    const start = Number(svg.dataset.failureStart);
+    const end = Number(svg.dataset.failureEnd);
+    let first = null;
+    for (const element of source.querySelectorAll("code span")) {
+        const from = Number(element.dataset.start);
+        const to = Number(element.dataset.end);
+        if (to <= start) {
+            element.classList.add("consumed");
+        }
+        else if (from >= end) {
+            element.classList.add("unconsumed");
+        }
+        else {
+            element.classList.add("failure");
+            first = first || element;
+        }
+    }
+    if (first) {
+        first.scrollIntoView({block: "center", inline: "nearest"});
+    }
+})();
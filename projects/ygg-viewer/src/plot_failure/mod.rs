@@ -0,0 +1,264 @@
+use std::collections::BTreeSet;
+
+use svg::{
+    node::element::{Group, Style, TSpan, Text, Title, SVG},
+    Document,
+};
+use yggdrasil_rt::{errors::YggdrasilErrorKind, TokenTree, YggdrasilError, YggdrasilRule};
+
+use shape_svg::ToSVG;
+use tree_layout::{Coordinate, Point, Rectangle};
+
+use crate::{
+    plot_html::{boundaries, source_pane, SOURCE_SCRIPT},
+    plot_svg::{escape_control, escape_xml, keep_spaces, SvgTree},
+    SvgPlotter,
+};
+
+/// Number of source lines shown before and after the failing line
+const CONTEXT: usize = 2;
+
+/// Where and why a parse failed, with the tree parsed so far if there is one.
+#[derive(Clone, Debug)]
+pub struct ParseFailure<'i, R>
+where
+    R: YggdrasilRule,
+{
+    source: &'i str,
+    start: usize,
+    end: usize,
+    expected: Vec<String>,
+    unexpected: Vec<String>,
+    message: Option<String>,
+    partial: Option<TokenTree<'i, R>>,
+}
+
+impl<'i, R> ParseFailure<'i, R>
+where
+    R: YggdrasilRule,
+{
+    /// A failure at a byte offset of the source, clamped to the nearest character boundary before it.
+    pub fn new(source: &'i str, offset: usize) -> Self {
+        let start = floor_boundary(source, offset);
+        Self { source, start, end: start, expected: vec![], unexpected: vec![], message: None, partial: None }
+    }
+    /// The failure reported by the parser for this source.
+    pub fn from_error(source: &'i str, error: &YggdrasilError<R>) -> Self {
+        let failure = Self::new(source, error.location.start).with_end(error.location.end);
+        match &error.variant {
+            YggdrasilErrorKind::InvalidRule { positives, negatives } => Self {
+                expected: positives.iter().map(|rule| format!("{:?}", rule)).collect(),
+                unexpected: negatives.iter().map(|rule| format!("{:?}", rule)).collect(),
+                ..failure
+            },
+            YggdrasilErrorKind::InvalidNode { expect } => failure.with_expected(format!("{:?}", expect)),
+            YggdrasilErrorKind::CustomError { message } => failure.with_message(message.as_str()),
+            // tags and kinds added by later runtimes are shown as the parser words them
+            variant => failure.with_message(variant.to_string()),
+        }
+    }
+    /// Set the end of the failing span, the failure is a single position by default.
+    pub fn with_end(self, end: usize) -> Self {
+        let end = floor_boundary(self.source, end).max(self.start);
+        Self { end, ..self }
+    }
+    /// Add a rule that was expected at the failure.
+    pub fn with_expected(mut self, rule: impl Into<String>) -> Self {
+        self.expected.push(rule.into());
+        self
+    }
+    /// Add a rule that was not allowed at the failure.
+    pub fn with_unexpected(mut self, rule: impl Into<String>) -> Self {
+        self.unexpected.push(rule.into());
+        self
+    }
+    /// Set a message shown instead of the expected rules.
+    pub fn with_message(self, message: impl Into<String>) -> Self {
+        Self { message: Some(message.into()), ..self }
+    }
+    /// Set the tree parsed before the failure, or the tree with recovery nodes, drawn above the source.
+    pub fn with_partial(self, tree: TokenTree<'i, R>) -> Self {
+        Self { partial: Some(tree), ..self }
+    }
+    /// Byte offset of the failure.
+    pub fn offset(&self) -> usize {
+        self.start
+    }
+    /// The source before the failure.
+    pub fn consumed(&self) -> &'i str {
+        &self.source[..self.start]
+    }
+    /// The source from the failure to the end.
+    pub fn unconsumed(&self) -> &'i str {
+        &self.source[self.start..]
+    }
+    /// 1-based line and column of the failure, in characters.
+    pub fn line_col(&self) -> (usize, usize) {
+        let consumed = self.consumed();
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        (line, column)
+    }
+    /// `line:column: expected A, B`, or the message of a custom error.
+    pub fn summary(&self) -> String {
+        let (line, column) = self.line_col();
+        let mut reasons = vec![];
+        if let Some(message) = &self.message {
+            reasons.push(message.clone());
+        }
+        if !self.expected.is_empty() {
+            reasons.push(format!("expected {}", self.expected.join(", ")));
+        }
+        if !self.unexpected.is_empty() {
+            reasons.push(format!("unexpected {}", self.unexpected.join(", ")));
+        }
+        if reasons.is_empty() {
+            reasons.push("failed to parse".to_string());
+        }
+        format!("{}:{}: {}", line, column, reasons.join("; "))
+    }
+    /// The failing text, at least one character unless the failure is at the end of the source.
+    fn failing(&self) -> (usize, usize) {
+        let end = match self.source[self.start..].chars().next() {
+            Some(c) if self.end == self.start => self.start + c.len_utf8(),
+            _ => self.end,
+        };
+        (self.start, end)
+    }
+}
+
+impl SvgPlotter {
+    /// Draw a failed parse: the partial tree if any, above the lines around the failure.
+    ///
+    /// The consumed source, the failing text and the unconsumed source get the classes `consumed`, `failure` and
+    /// `unconsumed`, the root carries the failing span in `data-failure-start` and `data-failure-end`.
+    pub fn draw_failure<R>(&self, failure: &ParseFailure<R>) -> SVG
+    where
+        R: YggdrasilRule,
+    {
+        let (mut body, tree, css, mut glyphs) = match &failure.partial {
+            Some(tree) => {
+                let (body, bbox, css, _, glyphs) = SvgTree::new(tree.clone(), self).measured().write_body();
+                (body, Some(bbox), css, glyphs)
+            }
+            None => (vec![], None, format!("{}\n{}", self.style, self.theme.base_css()), BTreeSet::new()),
+        };
+        let corner = match tree {
+            Some(tree) => Point::new(tree.min.x, tree.max.y + self.margin),
+            None => Point::new(0.0, 0.0),
+        };
+        let (panel, area) = self.failure_panel(failure, corner, &mut glyphs);
+        let mut bbox = tree.unwrap_or(area);
+        bbox |= area;
+        body.push(Box::new(panel));
+        let (start, end) = failure.failing();
+        let description = match failure.partial {
+            Some(_) => "The tree parsed before the failure, above the source lines around it",
+            None => "The source lines around the failure",
+        };
+        let document = self.titled(Document::new(), &failure.summary(), description);
+        let css = format!("{}\n{}{}", css, include_str!("failure.css"), self.font_css(&glyphs));
+        let mut document = document.add(Style::new(css));
+        for node in body {
+            document = document.add(node);
+        }
+        document
+            .set("font-size", self.metrics.font_size())
+            .set("viewBox", (bbox.min.x, bbox.min.y, bbox.width(), bbox.height()))
+            .set("data-failure-start", start)
+            .set("data-failure-end", end)
+    }
+
+    /// Draw the failure in the html playground of [`SvgPlotter::draw_playground`], the source pane is split into the
+    /// consumed and the unconsumed text.
+    pub fn draw_failure_html<R>(&self, failure: &ParseFailure<R>) -> String
+    where
+        R: YggdrasilRule,
+    {
+        let mut cuts = failure.partial.clone().map(boundaries).unwrap_or_default();
+        let (start, end) = failure.failing();
+        cuts.extend([start, end]);
+        let script = format!("{}\n{}", SOURCE_SCRIPT, include_str!("failure.js"));
+        self.page(&self.draw_failure(failure).to_string(), &source_pane(failure.source, cuts), &script)
+    }
+
+    /// The summary and the source lines around the failure, from the top left corner, their characters go to `glyphs`
+    fn failure_panel<R>(&self, failure: &ParseFailure<R>, corner: Point, glyphs: &mut BTreeSet<char>) -> (Group, Rectangle)
+    where
+        R: YggdrasilRule,
+    {
+        let metrics = &self.metrics;
+        let line_height = metrics.line_height();
+        let (start, end) = failure.failing();
+        let (failing_line, _) = failure.line_col();
+        let first = failing_line.saturating_sub(CONTEXT).max(1);
+        let last = failing_line + CONTEXT;
+        let gutter = metrics.line_width(&format!("{} ", last));
+        let mut group = Group::new().set("class", "parse-failure");
+        let mut width: Coordinate = metrics.line_width(&failure.summary());
+        let mut y = corner.y + line_height / 2.0;
+        let summary = Text::new().set("x", corner.x).set("y", y).set("class", "failure-message");
+        glyphs.extend(escape_control(&failure.summary()).chars());
+        group = group
+            .add(Title::new().add(svg::node::Text::new(escape_xml(&failure.summary()))))
+            .add(summary.add(svg::node::Text::new(escape_xml(&failure.summary()))));
+        let mut offset = 0;
+        for (index, line) in failure.source.split('\n').enumerate() {
+            let number = index + 1;
+            let line_start = offset;
+            offset += line.len() + 1;
+            if number < first {
+                continue;
+            }
+            if number > last {
+                break;
+            }
+            y += line_height;
+            let mut text = Text::new().set("x", corner.x).set("y", y).set("class", "source-line");
+            let gutter_text = format!("{} ", number);
+            glyphs.extend(gutter_text.chars());
+            text = text.add(TSpan::new().set("x", corner.x).set("class", "ln").add(svg::node::Text::new(gutter_text)));
+            let mut x = corner.x + gutter;
+            for (class, from, to) in [("consumed", line_start, start), ("failure", start, end), ("unconsumed", end, offset)] {
+                let from = from.clamp(line_start, line_start + line.len());
+                let to = to.clamp(from, line_start + line.len());
+                let shown = display(&failure.source[from..to]);
+                let span_width = metrics.line_width(&shown);
+                if class == "failure" && number == failing_line {
+                    // the end of a line or of the source is marked with a narrow box
+                    let mark_width = span_width.max(metrics.font_size() / 2.0);
+                    let mark = Rectangle {
+                        min: Point::new(x, y - line_height / 2.0),
+                        max: Point::new(x + mark_width, y + line_height / 2.0),
+                    };
+                    group = group.add(mark.to_svg().set("class", "failure-mark"));
+                }
+                // every piece is placed on its own, whitespace between the elements does not move it
+                if !shown.is_empty() {
+                    let shown = keep_spaces(&shown);
+                    glyphs.extend(shown.chars());
+                    let shown = escape_xml(&shown);
+                    text = text.add(TSpan::new().set("x", x).set("class", class).add(svg::node::Text::new(shown)));
+                }
+                x += span_width;
+            }
+            width = width.max(x - corner.x);
+            group = group.add(text);
+        }
+        let area = Rectangle { min: corner, max: Point::new(corner.x + width, y + line_height / 2.0) };
+        (group, area)
+    }
+}
+
+/// Tabs as spaces and control characters escaped, so that the measured width matches
+fn display(text: &str) -> String {
+    escape_control(&text.replace('\t', "    ").replace('\r', ""))
+}
+
+fn floor_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
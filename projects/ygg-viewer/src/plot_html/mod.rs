@@ -0,0 +1,48 @@
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use crate::SvgPlotter;
+
+pub(crate) use self::source::{boundaries, source_pane};
+
+mod source;
+
+const TEMPLATE: &str = include_str!("template.html");
+/// Links the source pane to the tree
+pub(crate) const SOURCE_SCRIPT: &str = include_str!("source.js");
+
+impl SvgPlotter {
+    /// Draw a standalone html page with the svg, supports pan, zoom, collapsing subtrees and search.
+    ///
+    /// All styles and scripts are embedded, the page does not fetch anything.
+    pub fn draw_html<R>(&self, tree: TokenTree<R>) -> String
+    where
+        R: YggdrasilRule,
+    {
+        self.html(tree, "", "")
+    }
+    /// Draw the html page of [`SvgPlotter::draw_html`] with the source text next to the tree.
+    ///
+    /// Hovering a node highlights its span in the source, clicking the source reveals the deepest node at that position.
+    pub fn draw_playground<R>(&self, source: &str, tree: TokenTree<R>) -> String
+    where
+        R: YggdrasilRule,
+    {
+        let pane = source_pane(source, boundaries(tree.clone()));
+        self.html(tree, &pane, SOURCE_SCRIPT)
+    }
+    fn html<R>(&self, tree: TokenTree<R>, pane: &str, script: &str) -> String
+    where
+        R: YggdrasilRule,
+    {
+        self.page(&self.draw(tree).to_string(), pane, script)
+    }
+    /// The viewer page around a drawn svg, `script` runs after the viewer.
+    pub(crate) fn page(&self, svg: &str, pane: &str, script: &str) -> String {
+        // the svg and the source are escaped, placeholders in the input text are never matched
+        TEMPLATE
+            .replace("/*{{style}}*/", include_str!("viewer.css"))
+            .replace("//{{script}}", &format!("{}\n{}", include_str!("viewer.js"), script))
+            .replace("<!--{{svg}}-->", svg)
+            .replace("<!--{{source}}-->", pane)
+    }
+}
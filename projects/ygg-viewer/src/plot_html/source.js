@@ -0,0 +1,79 @@
+(function () {
+    "use strict";
+    const viewer = window.cstViewer;
+    const source = document.getElementById("source");
+    if (!viewer || !source) {
+        return;
+    }
+    const svg = document.querySelector("#canvas svg");
+    const segments = Array.from(source.querySelectorAll("code span")).map(function (element) {
+        return {element: element, start: Number(element.dataset.start), end: Number(element.dataset.end)};
+    });
+
+    function depth(node) {
+        let depth = 0;
+        while (viewer.nodes.has(node.parent)) {
+            node = viewer.nodes.get(node.parent);
+            depth += 1;
+        }
+        return depth;
+    }
+
+    // highlight the span of the hovered node
+    let highlighted = [];
+    function highlight(node) {
+        for (const segment of highlighted) {
+            segment.element.classList.remove("span");
+        }
+        const start = Number(node.group.dataset.start);
+        const end = Number(node.group.dataset.end);
+        highlighted = segments.filter(function (segment) {
+            return start <= segment.start && segment.end <= end;
+        });
+        for (const segment of highlighted) {
+            segment.element.classList.add("span");
+        }
+        if (highlighted.length > 0) {
+            highlighted[0].element.scrollIntoView({block: "nearest", inline: "nearest"});
+        }
+    }
+
+    svg.addEventListener("mouseover", function (event) {
+        const group = event.target.closest && event.target.closest("g.cst-node");
+        if (group) {
+            highlight(viewer.nodes.get(group.dataset.id));
+        }
+    });
+
+    // reveal the deepest node enclosing the clicked position
+    let picked = null;
+    source.addEventListener("click", function (event) {
+        const element = event.target.closest("code span");
+        if (!element) {
+            return;
+        }
+        const offset = Number(element.dataset.start);
+        let best = null;
+        for (const node of viewer.nodes.values()) {
+            const start = Number(node.group.dataset.start);
+            const end = Number(node.group.dataset.end);
+            if (start <= offset && offset < end) {
+                const key = [end - start, -depth(node)];
+                if (!best || key[0] < best.key[0] || (key[0] === best.key[0] && key[1] < best.key[1])) {
+                    best = {node: node, key: key};
+                }
+            }
+        }
+        if (picked) {
+            picked.classList.remove("picked");
+        }
+        picked = element;
+        picked.classList.add("picked");
+        if (best) {
+            viewer.reveal(best.node);
+            viewer.focus(best.node.group);
+            viewer.show(best.node);
+            highlight(best.node);
+        }
+    });
+})();
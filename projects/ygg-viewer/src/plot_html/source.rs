@@ -0,0 +1,58 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+/// Start and end offsets of every pair in the tree, including ignored ones.
+pub(crate) fn boundaries<R>(tree: TokenTree<R>) -> BTreeSet<usize>
+where
+    R: YggdrasilRule,
+{
+    let mut out = BTreeSet::new();
+    let mut stack: Vec<_> = tree.into_iter().collect();
+    while let Some(pair) = stack.pop() {
+        let span = pair.get_span();
+        out.insert(span.start());
+        out.insert(span.end());
+        stack.extend(pair.into_inner());
+    }
+    out
+}
+
+/// The source text with line numbers, cut into `span`s at every boundary so that any node span can be highlighted.
+pub(crate) fn source_pane(source: &str, mut boundaries: BTreeSet<usize>) -> String {
+    let mut out = String::from("<pre id=\"source\">");
+    let mut start = 0;
+    for (index, line) in source.split('\n').enumerate() {
+        let end = start + line.len();
+        boundaries.insert(end);
+        write!(out, "<div class=\"line\"><span class=\"ln\">{}</span><code>", index + 1).unwrap();
+        let mut last = start;
+        // an empty line has no span, `start` itself closes nothing
+        for &offset in boundaries.range(start..=end) {
+            if offset == start || !source.is_char_boundary(offset) {
+                continue;
+            }
+            write!(out, "<span data-start=\"{}\" data-end=\"{}\">{}</span>", last, offset, escape_html(&source[last..offset]))
+                .unwrap();
+            last = offset;
+        }
+        out.push_str("</code></div>");
+        // skip the newline
+        start = end + 1;
+    }
+    out.push_str("</pre>");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,223 @@
+(function () {
+    "use strict";
+    const svg = document.querySelector("#canvas svg");
+    if (!svg) {
+        return;
+    }
+
+    // pan and zoom by moving the view box
+    const initial = svg.getAttribute("viewBox").split(/[\s,]+/).map(Number);
+    let view = initial.slice();
+
+    function applyView() {
+        svg.setAttribute("viewBox", view.join(" "));
+    }
+
+    function toSvg(clientX, clientY) {
+        const point = svg.createSVGPoint();
+        point.x = clientX;
+        point.y = clientY;
+        return point.matrixTransform(svg.getScreenCTM().inverse());
+    }
+
+    svg.addEventListener("wheel", function (event) {
+        event.preventDefault();
+        const scale = event.deltaY > 0 ? 1.15 : 1 / 1.15;
+        const p = toSvg(event.clientX, event.clientY);
+        view = [p.x - (p.x - view[0]) * scale, p.y - (p.y - view[1]) * scale, view[2] * scale, view[3] * scale];
+        applyView();
+    }, {passive: false});
+
+    let drag = null;
+    let dragged = false;
+    svg.addEventListener("pointerdown", function (event) {
+        drag = {x: event.clientX, y: event.clientY, view: view.slice(), ctm: svg.getScreenCTM()};
+        dragged = false;
+    });
+    window.addEventListener("pointermove", function (event) {
+        if (!drag) {
+            return;
+        }
+        const dx = event.clientX - drag.x;
+        const dy = event.clientY - drag.y;
+        if (Math.abs(dx) + Math.abs(dy) > 3) {
+            dragged = true;
+        }
+        view = [drag.view[0] - dx / drag.ctm.a, drag.view[1] - dy / drag.ctm.d, view[2], view[3]];
+        applyView();
+    });
+    window.addEventListener("pointerup", function () {
+        drag = null;
+    });
+
+    function fit() {
+        view = initial.slice();
+        applyView();
+    }
+
+    document.getElementById("fit").addEventListener("click", fit);
+
+    function focus(group) {
+        const box = group.getBBox();
+        const width = Math.max(box.width * 8, view[2] / 4);
+        const height = width * view[3] / view[2];
+        view = [box.x + box.width / 2 - width / 2, box.y + box.height / 2 - height / 2, width, height];
+        applyView();
+    }
+
+    // rebuild the tree from the data attributes
+    const nodes = new Map();
+    for (const group of svg.querySelectorAll("g.cst-node")) {
+        nodes.set(group.dataset.id, {group: group, parent: group.dataset.parent, children: [], edge: null, collapsed: false});
+    }
+    for (const node of nodes.values()) {
+        if (node.parent !== undefined && nodes.has(node.parent)) {
+            nodes.get(node.parent).children.push(node);
+        }
+    }
+    for (const edge of svg.querySelectorAll("line[data-child]")) {
+        const node = nodes.get(edge.dataset.child);
+        if (node) {
+            node.edge = edge;
+        }
+    }
+
+    function nodeOf(target) {
+        const group = target.closest && target.closest("g.cst-node");
+        return group ? nodes.get(group.dataset.id) : undefined;
+    }
+
+    // hide the descendants of collapsed nodes, iterative so that deep trees do not overflow the stack
+    function refresh(root) {
+        const stack = root.children.map(function (child) {
+            return [child, root.collapsed];
+        });
+        while (stack.length > 0) {
+            const [node, hidden] = stack.pop();
+            node.group.classList.toggle("hidden", hidden);
+            if (node.edge) {
+                node.edge.classList.toggle("hidden", hidden);
+            }
+            for (const child of node.children) {
+                stack.push([child, hidden || node.collapsed]);
+            }
+        }
+    }
+
+    function reveal(node) {
+        let parent = nodes.get(node.parent);
+        const collapsed = [];
+        while (parent) {
+            if (parent.collapsed) {
+                parent.collapsed = false;
+                parent.group.classList.remove("collapsed");
+                parent.group.setAttribute("aria-expanded", "true");
+                collapsed.push(parent);
+            }
+            parent = nodes.get(parent.parent);
+        }
+        // refresh from the top most expanded node
+        if (collapsed.length > 0) {
+            refresh(collapsed[collapsed.length - 1]);
+        }
+    }
+
+    svg.addEventListener("click", function (event) {
+        const node = nodeOf(event.target);
+        if (dragged || !node || node.children.length === 0) {
+            return;
+        }
+        node.collapsed = !node.collapsed;
+        node.group.classList.toggle("collapsed", node.collapsed);
+        node.group.setAttribute("aria-expanded", String(!node.collapsed));
+        refresh(node);
+    });
+
+    // side panel
+    const panel = {
+        rule: document.getElementById("rule"),
+        range: document.getElementById("range"),
+        bytes: document.getElementById("bytes"),
+        text: document.getElementById("text"),
+    };
+
+    function show(node) {
+        const data = node.group.dataset;
+        const title = node.group.querySelector("title");
+        panel.rule.textContent = data.rule;
+        panel.range.textContent = data.range;
+        panel.bytes.textContent = data.start + ".." + data.end;
+        panel.text.textContent = title ? title.textContent : "";
+    }
+
+    svg.addEventListener("mouseover", function (event) {
+        const node = nodeOf(event.target);
+        if (node) {
+            show(node);
+        }
+    });
+
+    // search by rule name or text
+    const search = document.getElementById("search");
+    const counter = document.getElementById("matches");
+    let matches = [];
+    let current = -1;
+
+    function select(index) {
+        if (current >= 0 && matches[current]) {
+            matches[current].group.classList.remove("current");
+        }
+        current = index;
+        const node = matches[current];
+        node.group.classList.add("current");
+        reveal(node);
+        focus(node.group);
+        show(node);
+        counter.textContent = (current + 1) + " / " + matches.length;
+    }
+
+    function runSearch() {
+        for (const node of matches) {
+            node.group.classList.remove("match", "current");
+        }
+        matches = [];
+        current = -1;
+        const query = search.value.trim().toLowerCase();
+        if (query.length === 0) {
+            counter.textContent = "";
+            return;
+        }
+        for (const node of nodes.values()) {
+            const title = node.group.querySelector("title");
+            const rule = node.group.dataset.rule.toLowerCase();
+            const text = node.children.length === 0 && title ? title.textContent.toLowerCase() : "";
+            if (rule.includes(query) || text.includes(query)) {
+                node.group.classList.add("match");
+                matches.push(node);
+            }
+        }
+        counter.textContent = matches.length + " matches";
+    }
+
+    let timer = 0;
+    search.addEventListener("input", function () {
+        clearTimeout(timer);
+        timer = setTimeout(runSearch, 150);
+    });
+    search.addEventListener("keydown", function (event) {
+        if (event.key !== "Enter") {
+            return;
+        }
+        clearTimeout(timer);
+        if (current < 0) {
+            runSearch();
+        }
+        if (matches.length > 0) {
+            const step = event.shiftKey ? matches.length - 1 : 1;
+            select((current + step) % matches.length);
+        }
+    });
+
+    // used by the source pane
+    window.cstViewer = {nodes: nodes, reveal: reveal, focus: focus, show: show};
+})();
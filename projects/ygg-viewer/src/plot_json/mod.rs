@@ -0,0 +1,141 @@
+#[cfg(feature = "yggdrasil")]
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::Coordinate;
+#[cfg(feature = "yggdrasil")]
+use tree_layout::Rectangle;
+
+use crate::NodeSpan;
+#[cfg(feature = "yggdrasil")]
+use crate::{plot_svg::SvgTree, NodeLabel, SvgPlotter};
+
+/// A laid out cst for front-ends that draw the tree themselves.
+///
+/// Coordinates are relative to the top left corner of the bounding box, see [`PositionedTree::SCHEMA`] for the json
+/// format. The types are plain data available without any feature, the `serde` feature derives their serialization
+/// and the `json` feature adds `PositionedTree::to_json`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionedTree {
+    /// version of the schema
+    pub version: u32,
+    /// width of the bounding box
+    pub width: Coordinate,
+    /// height of the bounding box
+    pub height: Coordinate,
+    /// nodes in pre-order, the id of a node is its index
+    pub nodes: Vec<PositionedNode>,
+    /// edges from parents to children
+    pub edges: Vec<PositionedEdge>,
+}
+
+/// A node of a [`PositionedTree`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionedNode {
+    /// index in [`PositionedTree::nodes`]
+    pub id: usize,
+    /// id of the parent, `None` for the root
+    pub parent: Option<usize>,
+    /// name of the rule
+    pub rule: String,
+    /// whether the node is a token
+    pub leaf: bool,
+    /// source text of a leaf
+    pub text: Option<String>,
+    /// text drawn in the node
+    pub label: String,
+    /// matched source text, a `+N` box spans all the nodes it stands for
+    pub span: NodeSpan,
+    /// box of the node
+    pub rect: NodeRect,
+    /// distance to the root
+    pub depth: usize,
+    /// ids of the children in source order
+    pub children: Vec<usize>,
+}
+
+/// Top left corner and size of a node
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeRect {
+    /// left side
+    pub x: Coordinate,
+    /// top side
+    pub y: Coordinate,
+    /// horizontal size
+    pub width: Coordinate,
+    /// vertical size
+    pub height: Coordinate,
+}
+
+/// A link of a [`PositionedTree`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionedEdge {
+    /// id of the parent
+    pub source: usize,
+    /// id of the child
+    pub target: usize,
+    /// polyline from the parent to the child
+    pub points: Vec<[Coordinate; 2]>,
+}
+
+impl PositionedTree {
+    /// Version of the schema written to [`PositionedTree::version`].
+    pub const VERSION: u32 = 1;
+    /// JSON schema of the serialized tree.
+    pub const SCHEMA: &'static str = include_str!("schema.json");
+    /// Serialize the tree, see [`PositionedTree::SCHEMA`].
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a positioned tree is always serializable")
+    }
+}
+
+#[cfg(feature = "yggdrasil")]
+impl SvgPlotter {
+    /// Layout the tree with the options of the svg, without drawing it.
+    pub fn draw_positioned<R>(&self, tree: TokenTree<R>) -> PositionedTree
+    where
+        R: YggdrasilRule,
+    {
+        let tree = SvgTree::new(tree, self);
+        let root = tree.arena();
+        let mut bbox: Option<Rectangle> = None;
+        for (node, _) in root.into_iter() {
+            *bbox.get_or_insert(node.boundary()) |= node.boundary();
+        }
+        let bbox = bbox.unwrap_or_else(Rectangle::empty);
+        let (dx, dy) = (bbox.min.x, bbox.min.y);
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        for (node, pair) in root.into_iter() {
+            let area = node.boundary();
+            let leaf = !pair.has_child(false);
+            if let Some(parent) = node.get_parent() {
+                let (start, end) = (parent.bottom_center(), node.top_center());
+                edges.push(PositionedEdge {
+                    source: parent.id,
+                    target: node.id,
+                    points: vec![[start.x - dx, start.y - dy], [end.x - dx, end.y - dy]],
+                });
+            }
+            nodes.push(PositionedNode {
+                id: node.id,
+                parent: node.get_parent().map(|p| p.id),
+                rule: format!("{:?}", pair.get_rule()),
+                leaf,
+                text: leaf.then(|| pair.get_string().to_string()),
+                label: tree.label(pair),
+                span: tree.span(pair).unwrap_or_else(|| NodeSpan::of(pair, &tree.lines)),
+                rect: NodeRect { x: area.min.x - dx, y: area.min.y - dy, width: area.width(), height: area.height() },
+                depth: node.depth(),
+                children: node.children.iter().map(|c| c.id).collect(),
+            });
+        }
+        nodes.sort_by_key(|node| node.id);
+        edges.sort_by_key(|edge| edge.target);
+        PositionedTree { version: PositionedTree::VERSION, width: bbox.width(), height: bbox.height(), nodes, edges }
+    }
+}
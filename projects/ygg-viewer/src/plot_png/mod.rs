@@ -0,0 +1,113 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use resvg::{
+    tiny_skia::{Color, Pixmap, Transform},
+    usvg::{fontdb::Database, Options, PostProcessingSteps, Tree, TreeParsing, TreePostProc},
+};
+#[cfg(feature = "yggdrasil")]
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use crate::{NodeLabel, SvgPlotter};
+
+/// Resolution and background of [`SvgPlotter::draw_png`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PngOptions {
+    dpi: f32,
+    background: Option<String>,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self { dpi: 96.0, background: None }
+    }
+}
+
+impl PngOptions {
+    /// Set the resolution, one pixel of the svg is one pixel of the image at 96 dpi.
+    pub fn with_dpi(self, dpi: f32) -> Self {
+        Self { dpi, ..self }
+    }
+    /// Fill the image with a css color, the background of the theme is used if not set.
+    pub fn with_background(self, background: impl Into<String>) -> Self {
+        Self { background: Some(background.into()), ..self }
+    }
+}
+
+/// Error of [`SvgPlotter::draw_png`]
+#[derive(Debug)]
+pub enum PngError {
+    /// The drawn svg could not be parsed
+    Svg(resvg::usvg::Error),
+    /// The background is not a css color
+    Background(String),
+    /// The image is empty or too large
+    Size {
+        /// width in pixels
+        width: f32,
+        /// height in pixels
+        height: f32,
+    },
+    /// The image could not be encoded
+    Encode(String),
+}
+
+impl Display for PngError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Svg(e) => write!(f, "invalid svg: {}", e),
+            Self::Background(color) => write!(f, "invalid background color: {}", color),
+            Self::Size { width, height } => write!(f, "can not create an image of {}x{} pixels", width, height),
+            Self::Encode(e) => write!(f, "can not encode png: {}", e),
+        }
+    }
+}
+
+impl Error for PngError {}
+
+impl SvgPlotter {
+    /// Rasterize the svg of [`SvgPlotter::draw`] to a png file.
+    ///
+    /// Text is rendered with the font of the metrics only, the output does not depend on the fonts of the system.
+    #[cfg(feature = "yggdrasil")]
+    pub fn draw_png<R>(&self, tree: TokenTree<R>, options: &PngOptions) -> Result<Vec<u8>, PngError>
+    where
+        R: YggdrasilRule,
+    {
+        self.rasterize(&self.draw(tree).to_string(), options)
+    }
+    /// Rasterize the svg of [`SvgPlotter::draw_tree`] to a png file, see [`SvgPlotter::draw_png`].
+    pub fn draw_tree_png<T>(&self, tree: T, options: &PngOptions) -> Result<Vec<u8>, PngError>
+    where
+        T: NodeLabel,
+    {
+        self.rasterize(&self.draw_tree(tree).to_string(), options)
+    }
+
+    fn rasterize(&self, svg: &str, options: &PngOptions) -> Result<Vec<u8>, PngError> {
+        let mut fonts = Database::new();
+        fonts.load_font_data(self.metrics.font_data().to_vec());
+        let family = fonts.faces().next().and_then(|face| face.families.first()).map(|(name, _)| name.clone());
+        let mut parse = Options::default();
+        if let Some(family) = family {
+            // every family in the stylesheet falls back to the loaded font
+            fonts.set_sans_serif_family(family.as_str());
+            parse.font_family = family;
+        }
+        parse.font_size = self.metrics.font_size() as f32;
+        let mut svg = Tree::from_str(svg, &parse).map_err(PngError::Svg)?;
+        svg.postprocess(PostProcessingSteps::default(), &fonts);
+
+        let scale = options.dpi / 96.0;
+        let (width, height) = (svg.size.width() * scale, svg.size.height() * scale);
+        let mut pixmap = Pixmap::new(width.ceil() as u32, height.ceil() as u32).ok_or(PngError::Size { width, height })?;
+        if let Some(background) = options.background.as_ref().or(self.theme.background.as_ref()) {
+            let color: svgtypes::Color = background.parse().map_err(|_| PngError::Background(background.clone()))?;
+            pixmap.fill(Color::from_rgba8(color.red, color.green, color.blue, color.alpha));
+        }
+        resvg::render(&svg, Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+        pixmap.encode_png().map_err(|e| PngError::Encode(e.to_string()))
+    }
+}
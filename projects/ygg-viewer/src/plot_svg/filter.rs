@@ -0,0 +1,113 @@
+use std::{collections::BTreeSet, ops::Deref};
+
+use yggdrasil_rt::{TokenPair, YggdrasilRule};
+
+use super::focus::Role;
+
+/// A pair of the cst as drawn, with the unary chain compressed into it
+#[derive(Clone, Debug)]
+pub(crate) struct CstNode<'i, R>
+where
+    R: YggdrasilRule,
+{
+    /// the innermost pair, whose children are drawn
+    pub pair: TokenPair<'i, R>,
+    /// names of the rules compressed above the pair, outermost first
+    pub chain: Vec<String>,
+    /// how the node is drawn in a focused tree
+    pub role: Role,
+    /// the last sibling a `+N` box stands for, the pair is the first one
+    pub last: Option<TokenPair<'i, R>>,
+}
+
+impl<'i, R> Deref for CstNode<'i, R>
+where
+    R: YggdrasilRule,
+{
+    type Target = TokenPair<'i, R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pair
+    }
+}
+
+impl<'i, R> CstNode<'i, R>
+where
+    R: YggdrasilRule,
+{
+    /// Rule names from the outermost to the innermost pair.
+    pub fn rules(&self) -> Vec<String> {
+        let mut rules = self.chain.clone();
+        rules.push(format!("{:?}", self.pair.get_rule()));
+        rules
+    }
+    /// The number of nodes a `+N` box stands for.
+    pub fn elided(&self) -> Option<usize> {
+        match self.role {
+            Role::Stub(elided) => Some(elided),
+            _ => None,
+        }
+    }
+}
+
+/// Which pairs of the cst are drawn, shared by the plotters of syntax trees
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CstFilter {
+    pub compress_chains: bool,
+    pub hidden_rules: BTreeSet<String>,
+    pub show_trivia: bool,
+}
+
+impl CstFilter {
+    /// The node of a pair, following its unary chain if compression is on.
+    pub fn cst_node<'i, R>(&self, mut pair: TokenPair<'i, R>) -> CstNode<'i, R>
+    where
+        R: YggdrasilRule,
+    {
+        let mut chain = vec![];
+        if self.compress_chains {
+            loop {
+                let mut children = self.visible_children(&pair);
+                // a token at the end of the chain keeps its own box
+                if children.len() != 1 || !children[0].has_child(false) {
+                    break;
+                }
+                chain.push(format!("{:?}", pair.get_rule()));
+                pair = children.remove(0);
+            }
+        }
+        CstNode { pair, chain, role: Role::Whole, last: None }
+    }
+    /// Children that are drawn, hidden rules are replaced by their own children.
+    pub fn visible_children<'i, R>(&self, pair: &TokenPair<'i, R>) -> Vec<TokenPair<'i, R>>
+    where
+        R: YggdrasilRule,
+    {
+        let mut out = vec![];
+        let mut stack: Vec<TokenPair<'i, R>> = vec![];
+        push_children(&mut stack, pair);
+        while let Some(pair) = stack.pop() {
+            if pair.get_rule().is_ignore() && !self.show_trivia {
+                continue;
+            }
+            if self.hidden_rules.contains(&format!("{:?}", pair.get_rule())) {
+                push_children(&mut stack, &pair);
+                continue;
+            }
+            out.push(pair);
+        }
+        out
+    }
+}
+
+/// Push the children in reverse, so that they pop in order
+fn push_children<'i, R>(stack: &mut Vec<TokenPair<'i, R>>, pair: &TokenPair<'i, R>)
+where
+    R: YggdrasilRule,
+{
+    let mut children = vec![];
+    for child in pair.clone().into_inner() {
+        children.push(child);
+    }
+    stack.extend(children.into_iter().rev());
+}
@@ -0,0 +1,186 @@
+use yggdrasil_rt::{TokenPair, YggdrasilRule};
+
+use super::{filter::CstNode, SvgPlotter};
+
+/// Draw only the deepest node covering a range of the source, its nearest ancestors and its nearest descendants.
+///
+/// Siblings of the ancestors and children below the depth limit are drawn as one `+N` box each, the ancestors above the
+/// limit as one breadcrumb row above the tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Focus {
+    range: FocusRange,
+    above: usize,
+    below: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FocusRange {
+    Bytes { start: usize, end: usize },
+    Lines { start: (usize, usize), end: (usize, usize) },
+}
+
+/// How a node is drawn in a focused tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Role {
+    /// with all descendants, the tree is not focused
+    Whole,
+    /// an ancestor of the focused node, with the byte range of the focus
+    Path((usize, usize)),
+    /// the focused node or one of its descendants, by depth below the focus
+    Below(usize),
+    /// a `+N` box standing for this many siblings or children
+    Stub(usize),
+}
+
+impl Focus {
+    /// Focus on the text between two byte offsets.
+    pub fn bytes(start: usize, end: usize) -> Self {
+        Self { range: FocusRange::Bytes { start, end: end.max(start) }, above: 3, below: 3 }
+    }
+    /// Focus on the text between two 1-based `(line, column)` positions, columns count characters.
+    pub fn lines(start: (usize, usize), end: (usize, usize)) -> Self {
+        Self { range: FocusRange::Lines { start, end: end.max(start) }, above: 3, below: 3 }
+    }
+    /// Set how many ancestors of the focused node are drawn, the path to the others is written above the tree.
+    pub fn with_above(self, above: usize) -> Self {
+        Self { above, ..self }
+    }
+    /// Set how many levels below the focused node are drawn, deeper children are drawn as one `+N` box.
+    pub fn with_below(self, below: usize) -> Self {
+        Self { below, ..self }
+    }
+    /// Byte offsets of the range in the text of the root
+    fn offsets<R>(&self, root: &TokenPair<R>) -> (usize, usize)
+    where
+        R: YggdrasilRule,
+    {
+        match self.range {
+            FocusRange::Bytes { start, end } => (start, end),
+            FocusRange::Lines { start, end } => {
+                let span = root.get_span();
+                let mut position = span.start_pos().line_column();
+                let (mut from, mut to) = (None, None);
+                for (index, c) in span.as_str().char_indices().chain([(span.as_str().len(), '\n')]) {
+                    let offset = span.start() + index;
+                    if from.is_none() && position >= start {
+                        from = Some(offset);
+                    }
+                    if to.is_none() && position >= end {
+                        to = Some(offset);
+                    }
+                    position = if c == '\n' { (position.0 + 1, 1) } else { (position.0, position.1 + 1) };
+                }
+                let from = from.unwrap_or(span.end());
+                (from, to.unwrap_or(span.end()).max(from))
+            }
+        }
+    }
+}
+
+/// Whether the pair matched the whole range, an empty range must be inside the pair
+fn covers<R>(pair: &TokenPair<R>, (start, end): (usize, usize)) -> bool
+where
+    R: YggdrasilRule,
+{
+    let span = pair.get_span();
+    span.start() <= start && end <= span.end() && (start < end || start < span.end())
+}
+
+impl SvgPlotter {
+    /// Draw only the deepest node covering the range, see [`Focus`].
+    pub fn with_focus(self, focus: Focus) -> Self {
+        Self { focus: Some(focus), ..self }
+    }
+    /// The top node drawn, the root or the highest ancestor within the limit
+    pub(crate) fn focus_root<'i, R>(&self, root: TokenPair<'i, R>) -> CstNode<'i, R>
+    where
+        R: YggdrasilRule,
+    {
+        self.focus_top(root).1
+    }
+    /// Rule names of the ancestors above the limit, outermost first
+    pub(crate) fn focus_breadcrumb<R>(&self, root: TokenPair<R>) -> Vec<String>
+    where
+        R: YggdrasilRule,
+    {
+        self.focus_top(root).0.iter().flat_map(|node| node.rules()).collect()
+    }
+    /// The ancestors above the limit and the top node drawn
+    fn focus_top<'i, R>(&self, root: TokenPair<'i, R>) -> (Vec<CstNode<'i, R>>, CstNode<'i, R>)
+    where
+        R: YggdrasilRule,
+    {
+        let Some(focus) = self.focus
+        else {
+            return (vec![], self.filter.cst_node(root));
+        };
+        let range = focus.offsets(&root);
+        let mut path = vec![self.filter.cst_node(root)];
+        while let Some(child) = self.covering(&path[path.len() - 1], range) {
+            path.push(child);
+        }
+        // the focused node is always drawn
+        let elided = path.len().saturating_sub(focus.above + 1);
+        let top = path.drain(elided..).next().expect("the focused node is in the path");
+        (path, self.focused(top, range))
+    }
+    /// The children drawn below a node of a focused tree
+    pub(crate) fn focus_children<'i, R>(&self, node: &CstNode<'i, R>) -> Vec<CstNode<'i, R>>
+    where
+        R: YggdrasilRule,
+    {
+        let children = || self.filter.visible_children(node).into_iter().map(|pair| self.filter.cst_node(pair));
+        match node.role {
+            Role::Whole => children().collect(),
+            Role::Stub(_) => vec![],
+            Role::Below(depth) => {
+                let children: Vec<_> = children().collect();
+                match children.first() {
+                    Some(first) if self.focus.is_some_and(|focus| depth >= focus.below) => {
+                        let last = children.last().map(|child| child.pair.clone());
+                        vec![CstNode { role: Role::Stub(children.len()), last, ..first.clone() }]
+                    }
+                    _ => children.into_iter().map(|child| CstNode { role: Role::Below(depth + 1), ..child }).collect(),
+                }
+            }
+            Role::Path(range) => {
+                let children: Vec<_> = children().collect();
+                let Some(index) = children.iter().position(|child| covers(child, range))
+                else {
+                    return vec![];
+                };
+                let mut out = vec![];
+                if index > 0 {
+                    let last = Some(children[index - 1].pair.clone());
+                    out.push(CstNode { role: Role::Stub(index), last, ..children[0].clone() });
+                }
+                let after = children.len() - index - 1;
+                let last = children.last().map(|child| child.pair.clone());
+                let mut children = children.into_iter().skip(index);
+                if let Some(child) = children.next() {
+                    out.push(self.focused(child, range));
+                }
+                if let Some(next) = children.next() {
+                    out.push(CstNode { role: Role::Stub(after), last, ..next });
+                }
+                out
+            }
+        }
+    }
+    /// The child of a node covering the range
+    fn covering<'i, R>(&self, node: &CstNode<'i, R>, range: (usize, usize)) -> Option<CstNode<'i, R>>
+    where
+        R: YggdrasilRule,
+    {
+        let child = self.filter.visible_children(node).into_iter().find(|child| covers(child, range))?;
+        Some(self.filter.cst_node(child))
+    }
+    /// An ancestor of the focused node, or the focused node itself
+    fn focused<'i, R>(&self, node: CstNode<'i, R>, range: (usize, usize)) -> CstNode<'i, R>
+    where
+        R: YggdrasilRule,
+    {
+        let role = if self.covering(&node, range).is_some() { Role::Path(range) } else { Role::Below(0) };
+        CstNode { role, ..node }
+    }
+}
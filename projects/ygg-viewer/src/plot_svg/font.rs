@@ -0,0 +1,70 @@
+use std::collections::BTreeSet;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use svg::{
+    node::element::{Path, Text},
+    Node,
+};
+
+use tree_layout::Point;
+
+use super::{escape_control, keep_spaces, node::text_lines, SvgPlotter};
+
+/// Family name of the embedded font, distinct from installed fonts of another version
+const EMBEDDED_FAMILY: &str = "ygg-viewer-labels";
+
+/// How the labels get their font, see [`SvgPlotter::with_font_embedding`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FontEmbedding {
+    /// Use the fonts of the stylesheet, which depend on the fonts installed where the svg is shown
+    #[default]
+    System,
+    /// Embed the font of the metrics as a base64 `@font-face`, with only the glyphs of the drawn characters
+    Subset,
+    /// Draw every label as `path` outlines of the font of the metrics, the text can not be selected
+    Outlines,
+}
+
+impl SvgPlotter {
+    /// Set how the labels get their font, the svg looks the same everywhere unless it is [`FontEmbedding::System`].
+    pub fn with_font_embedding(self, font_embedding: FontEmbedding) -> Self {
+        Self { font_embedding, ..self }
+    }
+    /// The `@font-face` of the characters drawn as text, if the font is embedded
+    pub(crate) fn font_css(&self, glyphs: &BTreeSet<char>) -> String {
+        match self.font_embedding {
+            FontEmbedding::Subset => {
+                let font = STANDARD.encode(self.metrics.subset(glyphs.iter().copied()));
+                format!(
+                    "@font-face {{ font-family: \"{0}\"; src: url(\"data:font/ttf;base64,{1}\") format(\"truetype\"); }}\nsvg text {{ font-family: \"{0}\", sans-serif; }}\n",
+                    EMBEDDED_FAMILY, font
+                )
+            }
+            FontEmbedding::System | FontEmbedding::Outlines => String::new(),
+        }
+    }
+    /// The lines of a label centered around `center`, as text or as outlines, the characters of a text go to `glyphs`
+    pub(crate) fn write_label(&self, class: &str, label: &str, center: Point, glyphs: &mut BTreeSet<char>) -> Box<dyn Node> {
+        let line_height = self.metrics.line_height();
+        match self.font_embedding {
+            FontEmbedding::Outlines => {
+                let lines: Vec<&str> = label.lines().collect();
+                let top = center.y - (lines.len().max(1) - 1) as f64 * line_height / 2.0;
+                let mut data = String::new();
+                for (i, line) in lines.iter().enumerate() {
+                    let x = center.x - self.metrics.line_width(line) / 2.0;
+                    let baseline = top + i as f64 * line_height + self.metrics.middle();
+                    data.push_str(&self.metrics.outline(line, x, baseline));
+                }
+                Box::new(Path::new().set("class", class).set("d", data))
+            }
+            FontEmbedding::System | FontEmbedding::Subset => {
+                for line in label.lines() {
+                    glyphs.extend(escape_control(&keep_spaces(line)).chars());
+                }
+                let text = Text::new().set("x", center.x).set("y", center.y).set("class", class);
+                Box::new(text_lines(text, label, center, line_height))
+            }
+        }
+    }
+}
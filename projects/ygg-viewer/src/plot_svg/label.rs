@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+/// How the text of a leaf is shown in its node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LabelFormat {
+    /// Show `⏎`, `→` and `·` for newlines, tabs and spaces, otherwise they are escaped or kept
+    pub visible_whitespace: bool,
+    /// Truncate longer labels with `…`, `0` for no limit
+    pub max_chars: usize,
+}
+
+impl Default for LabelFormat {
+    fn default() -> Self {
+        Self { visible_whitespace: true, max_chars: 32 }
+    }
+}
+
+impl LabelFormat {
+    /// Show or keep whitespace, see [`LabelFormat::visible_whitespace`].
+    pub fn with_visible_whitespace(self, visible_whitespace: bool) -> Self {
+        Self { visible_whitespace, ..self }
+    }
+    /// Set the length after which a label is truncated, `0` for no limit.
+    pub fn with_max_chars(self, max_chars: usize) -> Self {
+        Self { max_chars, ..self }
+    }
+    /// Format the text of a leaf, an empty token is shown as `ε`.
+    pub fn format(&self, text: &str) -> String {
+        if text.is_empty() {
+            return "ε".to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if self.visible_whitespace && chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    out.push('⏎');
+                }
+                '\n' if self.visible_whitespace => out.push('⏎'),
+                '\t' if self.visible_whitespace => out.push('→'),
+                ' ' if self.visible_whitespace => out.push('·'),
+                ' ' => out.push(' '),
+                // other spaces look like a normal space
+                c if c.is_whitespace() && self.visible_whitespace && !c.is_control() => escape_char(&mut out, c),
+                c if c.is_control() => escape_char(&mut out, c),
+                c => out.push(c),
+            }
+        }
+        truncate(out, self.max_chars)
+    }
+}
+
+/// The text with every control character but `\t`, `\n` and `\r` escaped, control characters are not valid in xml.
+pub(crate) fn escape_control(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => escape_char(&mut out, c),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The text as xml content or attribute value, with markup characters as entities and control characters escaped.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in escape_control(text).chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_char(out: &mut String, c: char) {
+    match c {
+        '\0' => out.push_str("\\0"),
+        '\t' => out.push_str("\\t"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        c => write!(out, "\\u{{{:x}}}", c as u32).unwrap(),
+    }
+}
+
+fn truncate(text: String, max_chars: usize) -> String {
+    if max_chars == 0 {
+        return text;
+    }
+    match text.char_indices().nth(max_chars) {
+        Some(_) => {
+            let end = text.char_indices().nth(max_chars - 1).map(|(i, _)| i).unwrap_or(text.len());
+            format!("{}…", &text[..end])
+        }
+        None => text,
+    }
+}
@@ -1,109 +1,180 @@
-use std::{borrow::Cow, cmp::max};
+use std::borrow::Cow;
 
-use shape_svg::ToSVG;
-use svg::{
-    node::element::{Text, SVG},
-    Document,
+use svg::node::element::SVG;
+#[cfg(feature = "yggdrasil")]
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::Coordinate;
+
+use crate::TextMetrics;
+
+#[cfg(feature = "yggdrasil")]
+pub use self::focus::Focus;
+#[cfg(feature = "yggdrasil")]
+pub(crate) use self::{
+    filter::{CstFilter, CstNode},
+    label::escape_xml,
+    node::{aria_label, shape},
+    tree::SvgTree,
+};
+pub use self::{
+    font::FontEmbedding,
+    label::LabelFormat,
+    node::NodeLabel,
+    span::{LineIndex, NodeSpan},
+    theme::{NodeShape, NodeStyle, Theme},
+};
+pub(crate) use self::{
+    label::escape_control,
+    node::{keep_spaces, Measured},
 };
-use yggdrasil_rt::{TokenPair, TokenTree, YggdrasilRule};
 
-use tree_layout::{Coordinate, LayoutConfig, Rectangle, TreeArena, TreeInfo};
+#[cfg(feature = "yggdrasil")]
+mod filter;
+#[cfg(feature = "yggdrasil")]
+mod focus;
+mod font;
+mod label;
+mod node;
+mod span;
+mod theme;
+#[cfg(feature = "yggdrasil")]
+mod tree;
 
 /// Plot a svg structure
 #[derive(Debug)]
 pub struct SvgPlotter {
-    style: Cow<'static, str>,
+    pub(crate) style: Cow<'static, str>,
+    pub(crate) metrics: TextMetrics,
+    pub(crate) theme: Theme,
+    #[cfg(feature = "yggdrasil")]
+    pub(crate) label_format: LabelFormat,
+    pub(crate) margin: Coordinate,
+    pub(crate) peer_margin: Coordinate,
+    pub(crate) layered: bool,
+    pub(crate) padding: (Coordinate, Coordinate),
+    pub(crate) corner_radius: Coordinate,
+    pub(crate) min_width: Coordinate,
+    pub(crate) id_prefix: String,
+    pub(crate) font_embedding: FontEmbedding,
+    #[cfg(feature = "yggdrasil")]
+    pub(crate) filter: CstFilter,
+    #[cfg(feature = "yggdrasil")]
+    pub(crate) focus: Option<Focus>,
 }
 
 impl Default for SvgPlotter {
     fn default() -> Self {
-        Self { style: include_str!("style.css").into() }
+        Self {
+            style: include_str!("style.css").into(),
+            metrics: TextMetrics::default(),
+            theme: Theme::default(),
+            #[cfg(feature = "yggdrasil")]
+            label_format: LabelFormat::default(),
+            margin: 12.0,
+            peer_margin: 4.0,
+            layered: true,
+            padding: (6.0, 1.0),
+            corner_radius: 5.0,
+            min_width: 36.0,
+            id_prefix: "cst-".to_string(),
+            font_embedding: FontEmbedding::System,
+            #[cfg(feature = "yggdrasil")]
+            filter: CstFilter::default(),
+            #[cfg(feature = "yggdrasil")]
+            focus: None,
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-struct SvgTree<'i, R>
-where
-    R: YggdrasilRule,
-{
-    cst: TokenTree<'i, R>,
-    svg: Document,
-}
-
-impl<'i, R> TreeInfo for SvgTree<'i, R>
-where
-    R: YggdrasilRule,
-{
-    type Node = TokenPair<'i, R>;
-
-    fn root(&self) -> Self::Node {
-        self.cst.clone().into_iter().next().unwrap()
+impl SvgPlotter {
+    /// Set the vertical space between a parent and its children, and the horizontal space between siblings.
+    pub fn with_margins(self, margin: Coordinate, peer_margin: Coordinate) -> Self {
+        Self { margin, peer_margin, ..self }
     }
-
-    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
-        let mut out = vec![];
-        for pair in node.clone().into_inner() {
-            if pair.get_rule().is_ignore() {
-                continue;
-            };
-            out.push(pair)
-        }
-        out.into_iter()
+    /// The vertical space between a parent and its children, and the horizontal space between siblings.
+    pub fn margins(&self) -> (Coordinate, Coordinate) {
+        (self.margin, self.peer_margin)
     }
-
-    fn width(&self, node: &Self::Node) -> Coordinate {
-        width_hint(node) * 12.0
+    /// Put all nodes of the same depth on one row, or pack every subtree as tight as possible.
+    pub fn with_layered(self, layered: bool) -> Self {
+        Self { layered, ..self }
     }
-    fn height(&self, _: &Self::Node) -> Coordinate {
-        20.0
+    /// Set the space between the label and the border of a node.
+    pub fn with_padding(self, horizontal: Coordinate, vertical: Coordinate) -> Self {
+        Self { padding: (horizontal, vertical), ..self }
     }
-}
-
-fn width_hint<R>(node: &TokenPair<R>) -> f64
-where
-    R: YggdrasilRule,
-{
-    let text = if node.has_child(false) { format!("{:?}", node.get_rule()) } else { node.get_string() };
-    max(text.len(), 3) as f64
-}
-
-impl<'i, R> SvgTree<'i, R>
-where
-    R: YggdrasilRule,
-{
-    fn write_svg(&mut self, plot: &SvgPlotter) -> SVG {
-        let mut document = Document::new().add(svg::node::element::Style::new(plot.style.to_string()));
-        let root = TreeArena::build(self.clone(), &LayoutConfig::new(12.0, 4.0).with_layered(true));
-        let mut bbox = Rectangle::empty();
-        for (node, pair) in root.into_iter() {
-            let area = node.boundary();
-            bbox &= area;
-            /// draw line
-            match root.get_link(&node) {
-                Some(line) => document = document.add(line.to_svg()),
-                None => {}
-            }
-            let mut text = Text::new().set("x", area.center().x).set("y", area.center().y);
-            if pair.has_child(false) {
-                text = text.add(svg::node::Text::new(format!("{:?}", pair.get_rule()))).set("class", "node");
-                document = document.add(area.to_svg().set("rx", 5).set("ry", 5).set("class", "node"));
-            }
-            else {
-                text = text.add(svg::node::Text::new(format!("{}", pair.get_string()))).set("class", "leaf");
-                document = document.add(area.to_svg().set("rx", 5).set("ry", 5).set("class", "leaf"));
-            }
-            document = document.add(text);
-        }
-        document.set("viewBox", (bbox.min.x, bbox.min.y, bbox.width(), bbox.height()))
+    /// The horizontal and vertical space between the label and the border of a node.
+    pub fn padding(&self) -> (Coordinate, Coordinate) {
+        self.padding
+    }
+    /// Set the font size of the labels in pixels.
+    pub fn with_font_size(self, font_size: Coordinate) -> Self {
+        Self { metrics: self.metrics.clone().with_font_size(font_size), ..self }
+    }
+    /// Measure the labels with another font, the font size is kept.
+    pub fn with_metrics(self, metrics: TextMetrics) -> Self {
+        let font_size = self.metrics.font_size();
+        Self { metrics: metrics.with_font_size(font_size), ..self }
+    }
+    /// Set the radius of the rounded corners of a node.
+    pub fn with_corner_radius(self, corner_radius: Coordinate) -> Self {
+        Self { corner_radius, ..self }
+    }
+    /// Set the minimum width of a node.
+    pub fn with_min_width(self, min_width: Coordinate) -> Self {
+        Self { min_width, ..self }
+    }
+    /// Set the colors and shapes of nodes, see [`Theme`].
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+    /// Replace the stylesheet embedded in the svg, the theme is appended to it.
+    pub fn with_style(self, style: impl Into<Cow<'static, str>>) -> Self {
+        Self { style: style.into(), ..self }
+    }
+    /// Draw a svg of any tree, see [`NodeLabel`]
+    pub fn draw_tree<T>(&self, tree: T) -> SVG
+    where
+        T: NodeLabel,
+    {
+        Measured { tree: &tree, plot: self }.write_svg()
     }
 }
 
+#[cfg(feature = "yggdrasil")]
 impl SvgPlotter {
+    /// Set how the text of leaves is shown, see [`LabelFormat`].
+    pub fn with_label_format(self, label_format: LabelFormat) -> Self {
+        Self { label_format, ..self }
+    }
+    /// Draw a chain of rules with a single child as one node labeled with all rule names.
+    pub fn with_compress_chains(mut self, compress_chains: bool) -> Self {
+        self.filter.compress_chains = compress_chains;
+        self
+    }
+    /// Do not draw nodes of this rule, their children are drawn in their place.
+    pub fn with_hidden_rule<R>(self, rule: R) -> Self
+    where
+        R: YggdrasilRule,
+    {
+        self.with_hidden_rule_name(format!("{:?}", rule))
+    }
+    /// Do not draw nodes whose rule has this debug name, their children are drawn in their place.
+    pub fn with_hidden_rule_name(mut self, rule: impl Into<String>) -> Self {
+        self.filter.hidden_rules.insert(rule.into());
+        self
+    }
+    /// Draw the ignored tokens such as whitespace and comments.
+    pub fn with_trivia(mut self, show_trivia: bool) -> Self {
+        self.filter.show_trivia = show_trivia;
+        self
+    }
     /// Draw a svg
     pub fn draw<R>(&self, tree: TokenTree<R>) -> SVG
     where
         R: YggdrasilRule,
     {
-        SvgTree { cst: tree, svg: Document::new() }.write_svg(self)
+        self.draw_tree(SvgTree::new(tree, self))
     }
 }
@@ -0,0 +1,283 @@
+use std::collections::BTreeSet;
+
+use shape_svg::ToSVG;
+use svg::{
+    node::element::{Description, Ellipse, Group, Style, TSpan, Text, Title, SVG},
+    Document, Node,
+};
+
+use tree_layout::{Coordinate, LayoutConfig, LayoutNode, Line, Point, Rectangle, TreeArena, TreeInfo};
+
+use super::{
+    label::escape_xml,
+    theme::{css_class, NodeShape},
+    NodeSpan, SvgPlotter,
+};
+
+/// What the plotter draws for every node of a tree.
+///
+/// The boxes are sized to fit the labels, the sizes given by [`TreeInfo`] are not used.
+#[allow(unused_variables)]
+pub trait NodeLabel: TreeInfo {
+    /// Text in the box of the node, one line per `\n`.
+    fn label(&self, node: &Self::Node) -> String;
+    /// Name the theme picks the style of the node by, `None` draws a placeholder as a dashed box.
+    fn rule(&self, node: &Self::Node) -> Option<String>;
+    /// Rule names stored in `data-rule`, outermost first, the rule by default.
+    fn rules(&self, node: &Self::Node) -> Vec<String> {
+        self.rule(node).into_iter().collect()
+    }
+    /// Whether the node is styled as a token, by default when it has no children.
+    fn is_leaf(&self, node: &Self::Node) -> bool {
+        self.children(node).next().is_none()
+    }
+    /// Full text shown when hovering the node, the label by default.
+    fn title(&self, node: &Self::Node) -> String {
+        self.label(node)
+    }
+    /// Source text matched by the node, stored in `data-start`, `data-end` and `data-range`.
+    fn span(&self, node: &Self::Node) -> Option<NodeSpan> {
+        None
+    }
+    /// Title of the document, the label of the root by default.
+    fn summary(&self) -> String {
+        self.label(&self.root()).replace('\n', " ")
+    }
+    /// Names of the ancestors of the root that are not drawn, written as one row above the root, none by default.
+    fn breadcrumb(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// A labeled tree with boxes sized by the metrics of the plotter
+#[derive(Debug)]
+pub(crate) struct Measured<'t, 'p, T> {
+    pub tree: &'t T,
+    pub plot: &'p SvgPlotter,
+}
+
+impl<'t, 'p, T> Clone for Measured<'t, 'p, T> {
+    fn clone(&self) -> Self {
+        Self { tree: self.tree, plot: self.plot }
+    }
+}
+
+impl<'t, 'p, T> TreeInfo for Measured<'t, 'p, T>
+where
+    T: NodeLabel,
+{
+    type Node = T::Node;
+
+    fn root(&self) -> Self::Node {
+        self.tree.root()
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        self.tree.children(node)
+    }
+
+    fn count(&self) -> usize {
+        self.tree.count()
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        let (width, _) = self.plot.metrics.text_size(&self.tree.label(node));
+        (width + 2.0 * self.plot.padding.0).max(self.plot.min_width)
+    }
+
+    fn height(&self, node: &Self::Node) -> Coordinate {
+        let (_, height) = self.plot.metrics.text_size(&self.tree.label(node));
+        height + 2.0 * self.plot.padding.1
+    }
+}
+
+impl<'t, 'p, T> Measured<'t, 'p, T>
+where
+    T: NodeLabel,
+{
+    /// Layout the tree with the margins of the plotter
+    pub fn arena(&self) -> TreeArena<Self> {
+        let layout = LayoutConfig::new(self.plot.margin, self.plot.peer_margin).with_layered(self.plot.layered);
+        TreeArena::build(self.clone(), &layout)
+    }
+
+    pub fn write_svg(&self) -> SVG {
+        let (body, bbox, css, description, glyphs) = self.write_body();
+        let css = css + &self.plot.font_css(&glyphs);
+        let mut document = self.plot.titled(Document::new(), &self.tree.summary(), &description).add(Style::new(css));
+        for node in body {
+            document = document.add(node);
+        }
+        document
+            .set("font-size", self.plot.metrics.font_size())
+            .set("viewBox", (bbox.min.x, bbox.min.y, bbox.width(), bbox.height()))
+    }
+
+    /// The edges and the tree of node groups, their bounding box, stylesheet, description and the characters of the labels
+    ///
+    /// Every node group is a `treeitem` owning the groups of its children, the edges are hidden from screen readers.
+    /// The description reads `N nodes in D levels, L of them leaves`.
+    pub fn write_body(&self) -> (Vec<Box<dyn Node>>, Rectangle, String, String, BTreeSet<char>) {
+        let plot = self.plot;
+        let tree = self.tree;
+        let root = self.arena();
+        let mut bbox: Option<Rectangle> = None;
+        let mut edges = Group::new().set("class", "cst-edges").set("aria-hidden", "true");
+        let mut items = Group::new().set("role", "tree").set("aria-label", escape_xml(&tree.summary()));
+        let mut rules = BTreeSet::new();
+        let mut glyphs = BTreeSet::new();
+        let (mut count, mut levels, mut leaves) = (0, 0, 0);
+        for (node, data) in root.into_iter() {
+            count += 1;
+            levels = levels.max(node.depth() + 1);
+            leaves += node.children.is_empty() as usize;
+            let area = node.boundary();
+            *bbox.get_or_insert(area) |= area;
+            if let Some(line) = root.get_link(node) {
+                edges = edges.add(line.to_svg().set("data-child", node.id));
+            }
+            let is_leaf = tree.is_leaf(data);
+            let (class, style) = match tree.rule(data) {
+                Some(rule) => {
+                    let class = format!("{} {}", if is_leaf { "leaf" } else { "node" }, escape_xml(&css_class(&rule)));
+                    let style = plot.theme.style_of(&rule, is_leaf).shape;
+                    rules.insert((rule, is_leaf));
+                    (class, style)
+                }
+                None => ("stub".to_string(), NodeShape::Rounded),
+            };
+            let rules = tree.rules(data).join(" > ");
+            let mut group = plot.tree_item(node, "cst-node", &aria_label(&rules, &tree.title(data)));
+            group = group.set("data-rule", escape_xml(&rules));
+            if let Some(span) = tree.span(data) {
+                group = group.set("data-start", span.start).set("data-end", span.end).set("data-range", span.range());
+            }
+            let group = group
+                .add(Title::new().add(svg::node::Text::new(escape_xml(&tree.title(data)))))
+                .add(shape(area, style, plot.corner_radius, &class))
+                .add(plot.write_label(&class, &tree.label(data), area.center(), &mut glyphs));
+            items = items.add(group);
+        }
+        let mut crumbs = None;
+        let breadcrumb = tree.breadcrumb();
+        if let (false, Some((top, _))) = (breadcrumb.is_empty(), root.into_iter().next()) {
+            // one line of text, a margin above the root and linked to it
+            let label = breadcrumb.join(" › ");
+            let (width, height) = plot.metrics.text_size(&label);
+            let bottom = top.boundary().min.y - plot.margin;
+            let center = Point::new(top.center.x, bottom - height / 2.0);
+            let row = Rectangle {
+                min: Point::new(center.x - width / 2.0, bottom - height),
+                max: Point::new(center.x + width / 2.0, bottom),
+            };
+            *bbox.get_or_insert(row) |= row;
+            edges = edges.add(Line::new(Point::new(center.x, bottom), top.top_center()).to_svg().set("class", "breadcrumb"));
+            crumbs = Some(plot.write_label("breadcrumb", &label, center, &mut glyphs));
+        }
+        let mut body: Vec<Box<dyn Node>> = vec![Box::new(edges), Box::new(items)];
+        body.extend(crumbs);
+        let mut css = format!("{}\n{}", plot.style, plot.theme.base_css());
+        for (rule, is_leaf) in rules.iter() {
+            css.push_str(&plot.theme.node_css(rule, *is_leaf));
+        }
+        let description = format!("{} nodes in {} levels, {} of them leaves", count, levels, leaves);
+        (body, bbox.unwrap_or_else(Rectangle::empty), css, description, glyphs)
+    }
+}
+
+/// The rules of a node and a short form of its text, the name read by screen readers
+pub(crate) fn aria_label(rules: &str, text: &str) -> String {
+    let text: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = match text.char_indices().nth(ARIA_TEXT) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    };
+    escape_xml(&if rules.is_empty() || text.is_empty() { format!("{}{}", rules, text) } else { format!("{}: {}", rules, text) })
+}
+
+/// Number of characters of the text in the name of a node
+const ARIA_TEXT: usize = 40;
+
+impl SvgPlotter {
+    /// Set the prefix of the ids of the title, the description and the nodes, to draw several trees in one page.
+    pub fn with_id_prefix(self, id_prefix: impl Into<String>) -> Self {
+        Self { id_prefix: id_prefix.into(), ..self }
+    }
+    /// The id of an element of the svg
+    pub(crate) fn element_id(&self, name: impl std::fmt::Display) -> String {
+        format!("{}{}", self.id_prefix, name)
+    }
+    /// The group of a node, a `treeitem` at its depth owning the groups of its children
+    pub(crate) fn tree_item(&self, node: &LayoutNode, class: &str, aria_label: &str) -> Group {
+        let mut group = Group::new()
+            .set("class", class)
+            .set("id", self.element_id(node.id))
+            .set("role", "treeitem")
+            .set("aria-level", node.depth() + 1)
+            .set("aria-label", aria_label)
+            .set("data-id", node.id);
+        if let Some(parent) = node.get_parent() {
+            group = group.set("data-parent", parent.id);
+        }
+        if !node.children.is_empty() {
+            let owns: Vec<String> = node.children.iter().map(|child| self.element_id(child.id)).collect();
+            group = group.set("aria-owns", owns.join(" ")).set("aria-expanded", "true");
+        }
+        group
+    }
+    /// Add the `title` and `desc` of a document, and refer to them as its name and description
+    pub(crate) fn titled(&self, document: SVG, title: &str, description: &str) -> SVG {
+        document
+            .set("aria-labelledby", self.element_id("title"))
+            .set("aria-describedby", self.element_id("desc"))
+            .add(Title::new().set("id", self.element_id("title")).add(svg::node::Text::new(escape_xml(title))))
+            .add(Description::new().set("id", self.element_id("desc")).add(svg::node::Text::new(escape_xml(description))))
+    }
+}
+
+/// The outline of a node
+pub(crate) fn shape(area: Rectangle, shape: NodeShape, corner_radius: Coordinate, class: &str) -> Box<dyn Node> {
+    let rounded = |radius: Coordinate| area.to_svg().set("rx", radius).set("ry", radius).set("class", class);
+    match shape {
+        NodeShape::Rounded => Box::new(rounded(corner_radius)),
+        NodeShape::Rectangle => Box::new(rounded(0.0)),
+        NodeShape::Pill => Box::new(rounded(area.height() / 2.0)),
+        NodeShape::Ellipse => Box::new(
+            Ellipse::new()
+                .set("cx", area.center().x)
+                .set("cy", area.center().y)
+                .set("rx", area.width() / 2.0)
+                .set("ry", area.height() / 2.0)
+                .set("class", class),
+        ),
+    }
+}
+
+/// One `tspan` per line, the lines are centered around `center`
+pub(crate) fn text_lines(mut text: Text, label: &str, center: Point, line_height: Coordinate) -> Text {
+    let lines: Vec<&str> = label.lines().collect();
+    let top = center.y - (lines.len().max(1) - 1) as Coordinate * line_height / 2.0;
+    for (i, line) in lines.iter().enumerate() {
+        let span = TSpan::new().set("x", center.x).set("y", top + i as Coordinate * line_height);
+        text = text.add(span.add(svg::node::Text::new(escape_xml(&keep_spaces(line)))));
+    }
+    text
+}
+
+/// The spaces svg would trim or collapse, at either end of the line or after another space, as no-break spaces
+pub(crate) fn keep_spaces(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let collapsed = |i: usize| i == 0 || i + 1 == chars.len() || chars[i - 1] == ' ';
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            if c == ' ' && collapsed(i) {
+                '\u{a0}'
+            }
+            else {
+                c
+            }
+        })
+        .collect()
+}
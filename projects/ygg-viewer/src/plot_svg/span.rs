@@ -0,0 +1,73 @@
+#[cfg(feature = "yggdrasil")]
+use yggdrasil_rt::{TokenPair, YggdrasilRule};
+
+/// Byte offsets and 1-based line and column range of the text matched by a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeSpan {
+    /// offset of the first byte
+    pub start: usize,
+    /// offset after the last byte
+    pub end: usize,
+    /// line of the first character
+    pub start_line: usize,
+    /// column of the first character, in characters
+    pub start_column: usize,
+    /// line after the last character
+    pub end_line: usize,
+    /// column after the last character, in characters
+    pub end_column: usize,
+}
+
+/// Start offsets of the lines of a text, built once to find the line and column of every node.
+#[derive(Clone, Debug)]
+pub struct LineIndex<'i> {
+    text: &'i str,
+    starts: Vec<usize>,
+}
+
+impl<'i> LineIndex<'i> {
+    /// The lines of the text, a line ends after `\n`.
+    pub fn new(text: &'i str) -> Self {
+        let starts = [0].into_iter().chain(text.match_indices('\n').map(|(offset, _)| offset + 1)).collect();
+        Self { text, starts }
+    }
+    /// 1-based line and column of the byte offset, columns count characters.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|&start| start <= offset);
+        (line, self.text[self.starts[line - 1]..offset].chars().count() + 1)
+    }
+}
+
+impl NodeSpan {
+    /// The span matched by the pair, in the lines of the input it was parsed from.
+    #[cfg(feature = "yggdrasil")]
+    pub fn of<R>(pair: &TokenPair<R>, lines: &LineIndex) -> Self
+    where
+        R: YggdrasilRule,
+    {
+        let span = pair.get_span();
+        let (start, end) = (span.start(), span.end());
+        let (start_line, start_column) = lines.line_column(start);
+        let (end_line, end_column) = lines.line_column(end);
+        Self { start, end, start_line, start_column, end_line, end_column }
+    }
+    /// The span from the start of the first pair to the end of the last one.
+    #[cfg(feature = "yggdrasil")]
+    pub fn between<R>(first: &TokenPair<R>, last: &TokenPair<R>, lines: &LineIndex) -> Self
+    where
+        R: YggdrasilRule,
+    {
+        let end = Self::of(last, lines);
+        Self { end: end.end, end_line: end.end_line, end_column: end.end_column, ..Self::of(first, lines) }
+    }
+    /// `line:column-line:column`
+    pub fn range(&self) -> String {
+        format!("{}:{}-{}:{}", self.start_line, self.start_column, self.end_line, self.end_column)
+    }
+    /// Whether the byte offset is inside the span.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
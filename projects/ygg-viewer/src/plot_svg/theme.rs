@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Write},
+    sync::Arc,
+};
+
+#[cfg(feature = "yggdrasil")]
+use yggdrasil_rt::YggdrasilRule;
+
+type RuleMapper = Arc<dyn Fn(&str, bool) -> Option<NodeStyle> + Send + Sync>;
+
+/// The outline of a node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodeShape {
+    /// Rectangle with the corner radius of the plotter
+    #[default]
+    Rounded,
+    /// Rectangle with sharp corners
+    Rectangle,
+    /// Rectangle with fully rounded left and right sides
+    Pill,
+    /// Ellipse inscribed in the node
+    Ellipse,
+}
+
+/// Colors and shape of a node, colors are any css color
+///
+/// A color with characters that could end the css declaration, such as `;`, `{`, `}` or a comment, is written as
+/// `initial`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeStyle {
+    /// background of the node
+    pub fill: String,
+    /// border of the node, `none` for no border
+    pub stroke: String,
+    /// color of the label
+    pub text: String,
+    /// outline of the node
+    pub shape: NodeShape,
+}
+
+impl NodeStyle {
+    /// A rounded node with the given colors.
+    pub fn new(fill: impl Into<String>, stroke: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { fill: fill.into(), stroke: stroke.into(), text: text.into(), shape: NodeShape::Rounded }
+    }
+    /// Set the outline of the node.
+    pub fn with_shape(self, shape: NodeShape) -> Self {
+        Self { shape, ..self }
+    }
+}
+
+/// Colors of a tree, with optional styles for single rules.
+///
+/// The style of a node is resolved in this order: the mapper, the rule table, the keyword, identifier and literal
+/// styles picked from the rule name, then the node or leaf style.
+#[derive(Clone)]
+pub struct Theme {
+    /// style of nodes with children
+    pub node: NodeStyle,
+    /// style of tokens
+    pub leaf: NodeStyle,
+    /// rules named `KW_*` or `*Keyword*`
+    pub keyword: Option<NodeStyle>,
+    /// rules named `*Identifier*`
+    pub identifier: Option<NodeStyle>,
+    /// rules named `*String*`, `*Number*`, `*Integer*`, `*Decimal*`, `*Boolean*` or `*Literal*`
+    pub literal: Option<NodeStyle>,
+    /// color of the links between nodes
+    pub edge: String,
+    /// background of the whole image, transparent if `None`
+    pub background: Option<String>,
+    /// styles by the debug name of a rule
+    pub rules: HashMap<String, NodeStyle>,
+    mapper: Option<RuleMapper>,
+}
+
+impl Debug for Theme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Theme")
+            .field("node", &self.node)
+            .field("leaf", &self.leaf)
+            .field("keyword", &self.keyword)
+            .field("identifier", &self.identifier)
+            .field("literal", &self.literal)
+            .field("edge", &self.edge)
+            .field("background", &self.background)
+            .field("rules", &self.rules)
+            .field("mapper", &self.mapper.is_some())
+            .finish()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    /// Black nodes and green tokens on a transparent background.
+    pub fn light() -> Self {
+        Self {
+            node: NodeStyle::new("black", "none", "white"),
+            leaf: NodeStyle::new("#77c63d", "none", "white"),
+            keyword: Some(NodeStyle::new("#c678dd", "none", "white")),
+            identifier: Some(NodeStyle::new("#4f8fd8", "none", "white")),
+            literal: Some(NodeStyle::new("#d19a66", "none", "white").with_shape(NodeShape::Pill)),
+            edge: "black".to_string(),
+            background: None,
+            rules: HashMap::new(),
+            mapper: None,
+        }
+    }
+    /// Light nodes on a dark background.
+    pub fn dark() -> Self {
+        Self {
+            node: NodeStyle::new("#3b4048", "#5c6370", "#e6e6e6"),
+            leaf: NodeStyle::new("#98c379", "none", "#1e2127"),
+            keyword: Some(NodeStyle::new("#c678dd", "none", "#1e2127")),
+            identifier: Some(NodeStyle::new("#61afef", "none", "#1e2127")),
+            literal: Some(NodeStyle::new("#e5c07b", "none", "#1e2127").with_shape(NodeShape::Pill)),
+            edge: "#abb2bf".to_string(),
+            background: Some("#1e2127".to_string()),
+            rules: HashMap::new(),
+            mapper: None,
+        }
+    }
+    /// Black and white with borders, categories differ by shape rather than by color.
+    pub fn high_contrast() -> Self {
+        Self {
+            node: NodeStyle::new("black", "black", "white").with_shape(NodeShape::Rectangle),
+            leaf: NodeStyle::new("white", "black", "black"),
+            keyword: Some(NodeStyle::new("white", "black", "black").with_shape(NodeShape::Rectangle)),
+            identifier: Some(NodeStyle::new("white", "black", "black").with_shape(NodeShape::Ellipse)),
+            literal: Some(NodeStyle::new("yellow", "black", "black").with_shape(NodeShape::Pill)),
+            edge: "black".to_string(),
+            background: Some("white".to_string()),
+            rules: HashMap::new(),
+            mapper: None,
+        }
+    }
+    /// Style every node of this rule.
+    #[cfg(feature = "yggdrasil")]
+    pub fn with_rule<R>(self, rule: R, style: NodeStyle) -> Self
+    where
+        R: YggdrasilRule,
+    {
+        self.with_rule_name(format!("{:?}", rule), style)
+    }
+    /// Style every node whose rule has this debug name.
+    pub fn with_rule_name(mut self, rule: impl Into<String>, style: NodeStyle) -> Self {
+        self.rules.insert(rule.into(), style);
+        self
+    }
+    /// Pick the style from the rule name and whether the node is a token, `None` falls back to the table.
+    pub fn with_mapper<F>(self, mapper: F) -> Self
+    where
+        F: Fn(&str, bool) -> Option<NodeStyle> + Send + Sync + 'static,
+    {
+        Self { mapper: Some(Arc::new(mapper)), ..self }
+    }
+    /// Resolve the style of a node.
+    pub fn style_of(&self, rule: &str, is_leaf: bool) -> NodeStyle {
+        if let Some(style) = self.mapper.as_ref().and_then(|mapper| mapper(rule, is_leaf)) {
+            return style;
+        }
+        if let Some(style) = self.rules.get(rule) {
+            return style.clone();
+        }
+        let category = if rule.starts_with("KW_") || rule.contains("Keyword") {
+            &self.keyword
+        }
+        else if rule.contains("Identifier") {
+            &self.identifier
+        }
+        else if ["String", "Number", "Integer", "Decimal", "Boolean", "Literal"].iter().any(|s| rule.contains(s)) {
+            &self.literal
+        }
+        else {
+            &None
+        };
+        match category {
+            Some(style) => style.clone(),
+            None if is_leaf => self.leaf.clone(),
+            None => self.node.clone(),
+        }
+    }
+    /// Css of the edges and background.
+    pub(crate) fn base_css(&self) -> String {
+        let mut css = format!("svg line {{ stroke: {}; }}\n", css_color(&self.edge));
+        if let Some(background) = &self.background {
+            writeln!(css, "svg {{ background: {}; }}", css_color(background)).ok();
+        }
+        css
+    }
+    /// Css of the nodes with this rule and kind.
+    pub(crate) fn node_css(&self, rule: &str, is_leaf: bool) -> String {
+        let style = self.style_of(rule, is_leaf);
+        let selector = format!(".{}.{}", if is_leaf { "leaf" } else { "node" }, css_escape(&css_class(rule)));
+        format!(
+            "svg rect{0}, svg ellipse{0} {{ fill: {1}; stroke: {2}; }}\nsvg text{0}, svg path{0} {{ fill: {3}; }}\n",
+            selector,
+            css_color(&style.fill),
+            css_color(&style.stroke),
+            css_color(&style.text)
+        )
+    }
+}
+
+/// The class of a rule, the rule name with whitespace, control characters and `%` percent-encoded so that distinct rules
+/// never share a class
+pub(crate) fn css_class(rule: &str) -> String {
+    let mut out = String::with_capacity(rule.len());
+    for c in rule.chars() {
+        if c.is_whitespace() || c.is_control() || c == '%' {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                write!(out, "%{:02X}", byte).ok();
+            }
+        }
+        else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The class as a css identifier, other ascii characters are hex escapes which are also safe in the `style` element
+fn css_escape(class: &str) -> String {
+    let mut out = String::with_capacity(class.len());
+    for (i, c) in class.chars().enumerate() {
+        let leading_digit = c.is_ascii_digit() && (i == 0 || i == 1 && class.starts_with('-'));
+        if !c.is_ascii() || (c.is_ascii_alphanumeric() || c == '_' || c == '-') && !leading_digit {
+            out.push(c);
+        }
+        else {
+            write!(out, "\\{:x} ", c as u32).ok();
+        }
+    }
+    if class == "-" {
+        out = "\\-".to_string();
+    }
+    out
+}
+
+/// The color if it can not end its declaration or the rule, `initial` otherwise
+fn css_color(color: &str) -> &str {
+    let safe = color.chars().all(|c| c.is_ascii_alphanumeric() || " #(),.%+-/_".contains(c));
+    if safe && !color.contains("/*") && !color.trim().is_empty() {
+        color
+    }
+    else {
+        "initial"
+    }
+}
@@ -0,0 +1,122 @@
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::{TreeArena, TreeInfo};
+
+use super::{filter::CstNode, node::Measured, LineIndex, NodeLabel, NodeSpan, SvgPlotter};
+
+/// The concrete syntax tree of a yggdrasil parser, as filtered by the plotter
+#[derive(Clone, Debug)]
+pub(crate) struct SvgTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    pub cst: TokenTree<'i, R>,
+    pub plot: &'p SvgPlotter,
+    /// lines of the parsed input, shared by the spans of every node
+    pub lines: LineIndex<'i>,
+}
+
+impl<'i, 'p, R> TreeInfo for SvgTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    type Node = CstNode<'i, R>;
+
+    fn root(&self) -> Self::Node {
+        self.plot.focus_root(self.cst.clone().into_iter().next().unwrap())
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        self.plot.focus_children(node).into_iter()
+    }
+}
+
+impl<'i, 'p, R> NodeLabel for SvgTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    /// The rule names of a node, one per line, the formatted text of a leaf
+    fn label(&self, node: &Self::Node) -> String {
+        if let Some(elided) = node.elided() {
+            format!("+{}", elided)
+        }
+        else if node.has_child(false) {
+            node.rules().join("\n")
+        }
+        else {
+            self.plot.label_format.format(&node.get_string())
+        }
+    }
+    /// The innermost rule, the box of elided nodes has no rule
+    fn rule(&self, node: &Self::Node) -> Option<String> {
+        match node.elided() {
+            Some(_) => None,
+            None => Some(format!("{:?}", node.get_rule())),
+        }
+    }
+    fn rules(&self, node: &Self::Node) -> Vec<String> {
+        match node.elided() {
+            Some(_) => vec![],
+            None => node.rules(),
+        }
+    }
+    fn is_leaf(&self, node: &Self::Node) -> bool {
+        !node.has_child(false)
+    }
+    /// The full text of the span, the label may be truncated
+    fn title(&self, node: &Self::Node) -> String {
+        match node.elided() {
+            Some(elided) => format!("{} nodes not drawn", elided),
+            None => node.get_string().to_string(),
+        }
+    }
+    fn span(&self, node: &Self::Node) -> Option<NodeSpan> {
+        match &node.last {
+            Some(last) => Some(NodeSpan::between(node, last, &self.lines)),
+            None => Some(NodeSpan::of(node, &self.lines)),
+        }
+    }
+    /// The rules above the focused tree
+    fn breadcrumb(&self) -> Vec<String> {
+        match self.cst.clone().next() {
+            Some(root) => self.plot.focus_breadcrumb(root),
+            None => vec![],
+        }
+    }
+    /// The rule of the root and the start of the parsed text
+    fn summary(&self) -> String {
+        match self.cst.clone().into_iter().next() {
+            Some(root) => {
+                let text = root.get_string().split_whitespace().collect::<Vec<_>>().join(" ");
+                let text = match text.char_indices().nth(SUMMARY_TEXT) {
+                    Some((end, _)) => format!("{}…", &text[..end]),
+                    None => text,
+                };
+                format!("Syntax tree of {:?}: {}", root.get_rule(), text)
+            }
+            None => "Empty syntax tree".to_string(),
+        }
+    }
+}
+
+/// Number of characters of the parsed text in the title of the document
+const SUMMARY_TEXT: usize = 80;
+
+impl<'i, 'p, R> SvgTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    /// The tree drawn by the plotter, the lines of the input are indexed once
+    pub fn new(cst: TokenTree<'i, R>, plot: &'p SvgPlotter) -> Self {
+        let input = cst.clone().next().map_or("", |root| root.get_span().get_input());
+        Self { cst, plot, lines: LineIndex::new(input) }
+    }
+    /// The tree with boxes sized by the plotter
+    pub fn measured(&self) -> Measured<'_, 'p, Self> {
+        Measured { tree: self, plot: self.plot }
+    }
+    /// Layout the tree with the margins of the plotter
+    pub fn arena(&self) -> TreeArena<Measured<'_, 'p, Self>> {
+        self.measured().arena()
+    }
+}
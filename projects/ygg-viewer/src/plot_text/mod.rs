@@ -0,0 +1,398 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use yggdrasil_rt::{TokenTree, YggdrasilRule};
+
+use tree_layout::{Coordinate, LayoutConfig, TreeArena, TreeInfo};
+
+use crate::{
+    plot_svg::{CstFilter, CstNode},
+    LabelFormat, Theme,
+};
+
+/// Rows of a node, the label between two borders
+const NODE_HEIGHT: usize = 3;
+
+/// What to do when the tree is wider than [`TextPlotter::with_max_width`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Cut the picture into vertical strips printed one after another
+    #[default]
+    Scroll,
+    /// Replace the deepest subtrees with `+N` stubs until the tree fits, then scroll
+    Fold,
+}
+
+/// Draw a tree with unicode box-drawing characters, for terminals and test output.
+#[derive(Clone, Debug)]
+pub struct TextPlotter {
+    theme: Theme,
+    label_format: LabelFormat,
+    colored: bool,
+    max_width: Option<usize>,
+    overflow: Overflow,
+    filter: CstFilter,
+}
+
+impl Default for TextPlotter {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            label_format: LabelFormat::default(),
+            colored: false,
+            max_width: None,
+            overflow: Overflow::Scroll,
+            filter: CstFilter::default(),
+        }
+    }
+}
+
+impl TextPlotter {
+    /// Set the colors used with [`TextPlotter::with_colored`].
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+    /// Set how the text of leaves is shown, see [`LabelFormat`].
+    pub fn with_label_format(self, label_format: LabelFormat) -> Self {
+        Self { label_format, ..self }
+    }
+    /// Color the nodes with ansi escape codes.
+    pub fn with_colored(self, colored: bool) -> Self {
+        Self { colored, ..self }
+    }
+    /// Limit the width of every line in columns, see [`Overflow`].
+    pub fn with_max_width(self, max_width: usize) -> Self {
+        Self { max_width: Some(max_width), ..self }
+    }
+    /// Set what to do when the tree is too wide.
+    pub fn with_overflow(self, overflow: Overflow) -> Self {
+        Self { overflow, ..self }
+    }
+    /// Draw a chain of rules with a single child as one node labeled with all rule names.
+    pub fn with_compress_chains(mut self, compress_chains: bool) -> Self {
+        self.filter.compress_chains = compress_chains;
+        self
+    }
+    /// Do not draw nodes of this rule, their children are drawn in their place.
+    pub fn with_hidden_rule<R>(self, rule: R) -> Self
+    where
+        R: YggdrasilRule,
+    {
+        self.with_hidden_rule_name(format!("{:?}", rule))
+    }
+    /// Do not draw nodes whose rule has this debug name, their children are drawn in their place.
+    pub fn with_hidden_rule_name(mut self, rule: impl Into<String>) -> Self {
+        self.filter.hidden_rules.insert(rule.into());
+        self
+    }
+    /// Draw the ignored tokens such as whitespace and comments.
+    pub fn with_trivia(mut self, show_trivia: bool) -> Self {
+        self.filter.show_trivia = show_trivia;
+        self
+    }
+    /// Draw the tree as lines of text.
+    pub fn draw<R>(&self, tree: TokenTree<R>) -> String
+    where
+        R: YggdrasilRule,
+    {
+        let mut grid = self.draw_grid(TextTree { cst: tree.clone(), plot: self, fold: usize::MAX });
+        if let (Some(max_width), Overflow::Fold) = (self.max_width, self.overflow) {
+            let mut fold = grid.depth.saturating_sub(1);
+            while grid.width() > max_width && fold > 1 {
+                fold -= 1;
+                grid = self.draw_grid(TextTree { cst: tree.clone(), plot: self, fold });
+            }
+        }
+        grid.render(self.max_width.unwrap_or(usize::MAX))
+    }
+
+    fn draw_grid<R>(&self, tree: TextTree<R>) -> Grid
+    where
+        R: YggdrasilRule,
+    {
+        // one cell is one unit, the gaps leave a row for the connectors
+        let layout = LayoutConfig::new(1.0, 2.0).with_layered(true);
+        let arena = TreeArena::build(tree.clone(), &layout);
+        let mut boxes = vec![];
+        let (mut min_x, mut min_y) = (Coordinate::INFINITY, Coordinate::INFINITY);
+        for (node, _) in arena.into_iter() {
+            min_x = min_x.min(node.center.x - node.width / 2.0);
+            min_y = min_y.min(node.center.y - node.height / 2.0);
+        }
+        let mut grid = Grid::default();
+        for (node, (cst, depth)) in arena.into_iter() {
+            let left = (node.center.x - node.width / 2.0 - min_x).round() as usize;
+            let top = (node.center.y - node.height / 2.0 - min_y).round() as usize;
+            let width = node.width as usize;
+            // from the center rather than the rounded box, so that a single child lines up with its parent
+            let column = ((node.center.x - min_x).floor() as usize).clamp(left + 1, left + width - 2);
+            boxes.push((node.id, node.children.iter().map(|c| c.id).collect::<Vec<_>>(), column, top));
+            let color = self.colored.then(|| {
+                let rule = format!("{:?}", cst.get_rule());
+                let style = self.theme.style_of(&rule, !cst.has_child(false));
+                (rgb(&style.fill), rgb(&style.text))
+            });
+            grid.draw_box(left, top, width, &tree.label(&(cst.clone(), *depth)), color);
+            grid.depth = grid.depth.max(*depth + 1);
+        }
+        // connectors between the bottom of a parent and the tops of its children
+        for (_, children, parent, top) in boxes.iter() {
+            if children.is_empty() {
+                continue;
+            }
+            let (row, parent) = (top + NODE_HEIGHT, *parent);
+            grid.set(parent, row - 1, '┬');
+            let mut columns = vec![];
+            for child in children {
+                if let Some((_, _, column, _)) = boxes.iter().find(|b| b.0 == *child) {
+                    columns.push(*column);
+                    grid.set(*column, row + 1, '┴');
+                }
+            }
+            let min = columns.iter().copied().chain([parent]).min().unwrap_or(parent);
+            let max = columns.iter().copied().chain([parent]).max().unwrap_or(parent);
+            for column in min..=max {
+                let up = column == parent;
+                let down = columns.contains(&column);
+                grid.set(column, row, junction(up, down, column > min, column < max));
+            }
+        }
+        grid
+    }
+}
+
+/// The box-drawing character joining lines in these directions
+fn junction(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (true, true, true, true) => '┼',
+        (true, true, true, false) => '┤',
+        (true, true, false, true) => '├',
+        (true, true, false, false) => '│',
+        (true, false, true, true) => '┴',
+        (true, false, true, false) => '┘',
+        (true, false, false, true) => '└',
+        (false, true, true, true) => '┬',
+        (false, true, true, false) => '┐',
+        (false, true, false, true) => '┌',
+        (true, false, false, false) | (false, true, false, false) => '│',
+        _ => '─',
+    }
+}
+
+/// `#rgb`, `#rrggbb` and a few color names
+fn rgb(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = |s: &str| u8::from_str_radix(s, 16).ok();
+    match color.trim().to_ascii_lowercase().as_str() {
+        "black" => Some((0, 0, 0)),
+        "white" => Some((255, 255, 255)),
+        "red" => Some((255, 0, 0)),
+        "green" => Some((0, 128, 0)),
+        "blue" => Some((0, 0, 255)),
+        "yellow" => Some((255, 255, 0)),
+        "gray" | "grey" => Some((128, 128, 128)),
+        s if s.len() == 7 && s.starts_with('#') => Some((hex(&s[1..3])?, hex(&s[3..5])?, hex(&s[5..7])?)),
+        s if s.len() == 4 && s.starts_with('#') => {
+            let (r, g, b) = (hex(&s[1..2])?, hex(&s[2..3])?, hex(&s[3..4])?);
+            Some((r * 17, g * 17, b * 17))
+        }
+        _ => None,
+    }
+}
+
+type Rgb = Option<(u8, u8, u8)>;
+
+#[derive(Clone)]
+struct Cell {
+    char: char,
+    /// zero-width characters following the char, such as combining accents
+    marks: String,
+    /// background and foreground
+    color: Option<(Rgb, Rgb)>,
+}
+
+impl Cell {
+    fn new(char: char, color: Option<(Rgb, Rgb)>) -> Self {
+        Self { char, marks: String::new(), color }
+    }
+}
+
+/// The second half of a wide character
+const WIDE: char = '\0';
+
+#[derive(Default)]
+struct Grid {
+    rows: Vec<Vec<Cell>>,
+    depth: usize,
+}
+
+impl Grid {
+    fn width(&self) -> usize {
+        self.rows.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+    fn put(&mut self, x: usize, y: usize, cell: Cell) {
+        if self.rows.len() <= y {
+            self.rows.resize(y + 1, vec![]);
+        }
+        let row = &mut self.rows[y];
+        if row.len() <= x {
+            row.resize(x + 1, Cell::new(' ', None));
+        }
+        row[x] = cell;
+    }
+    fn set(&mut self, x: usize, y: usize, char: char) {
+        let color = self.rows.get(y).and_then(|row| row.get(x)).and_then(|cell| cell.color);
+        self.put(x, y, Cell::new(char, color));
+    }
+    fn draw_box(&mut self, left: usize, top: usize, width: usize, label: &str, color: Option<(Rgb, Rgb)>) {
+        let border = color.map(|(fill, _)| (None, fill));
+        let right = left + width - 1;
+        for x in left + 1..right {
+            self.put(x, top, Cell::new('─', border));
+            self.put(x, top + 2, Cell::new('─', border));
+        }
+        for (x, y, char) in [(left, top, '╭'), (right, top, '╮'), (left, top + 2, '╰'), (right, top + 2, '╯')] {
+            self.put(x, y, Cell::new(char, border));
+        }
+        self.put(left, top + 1, Cell::new('│', border));
+        self.put(right, top + 1, Cell::new('│', border));
+        // the label is centered on a filled background
+        let inner = width - 2;
+        let mut x = left + 1 + (inner - label.width()) / 2;
+        for fill in left + 1..right {
+            self.put(fill, top + 1, Cell::new(' ', color));
+        }
+        for char in label.chars() {
+            let size = char.width().unwrap_or(0);
+            if size == 0 {
+                // marks stay with the char before them, a leading mark sits on the padding
+                let base = if self.rows[top + 1][x - 1].char == WIDE { x - 2 } else { x - 1 };
+                self.rows[top + 1][base].marks.push(char);
+                continue;
+            }
+            self.put(x, top + 1, Cell::new(char, color));
+            if size == 2 {
+                self.put(x + 1, top + 1, Cell::new(WIDE, color));
+            }
+            x += size;
+        }
+    }
+    /// All rows, cut into strips of at most `max_width` columns
+    fn render(&self, max_width: usize) -> String {
+        let width = self.width();
+        let max_width = max_width.max(1);
+        let mut out = String::new();
+        let mut start = 0;
+        while start < width {
+            if start > 0 {
+                out.push('\n');
+            }
+            let mut end = (start + max_width).min(width);
+            // never cut a wide character in half
+            while end < width && self.rows.iter().any(|row| row.get(end).is_some_and(|cell| cell.char == WIDE)) {
+                end -= 1;
+            }
+            for row in self.rows.iter() {
+                let mut line = String::new();
+                let mut color = None;
+                for cell in row.iter().take(end).skip(start) {
+                    if cell.char == WIDE {
+                        continue;
+                    }
+                    if cell.color != color {
+                        line.push_str(&escape(cell.color));
+                        color = cell.color;
+                    }
+                    line.push(cell.char);
+                    line.push_str(&cell.marks);
+                }
+                if color.is_some() {
+                    line.push_str("\x1b[0m");
+                }
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+            start = end;
+        }
+        out
+    }
+}
+
+fn escape(color: Option<(Rgb, Rgb)>) -> String {
+    let mut out = String::from("\x1b[0m");
+    if let Some((background, foreground)) = color {
+        if let Some((r, g, b)) = background {
+            out.push_str(&format!("\x1b[48;2;{};{};{}m", r, g, b));
+        }
+        if let Some((r, g, b)) = foreground {
+            out.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+        }
+    }
+    out
+}
+
+/// The cst with sizes in cells, nodes at `fold` depth hide their children
+#[derive(Clone, Debug)]
+struct TextTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    cst: TokenTree<'i, R>,
+    plot: &'p TextPlotter,
+    fold: usize,
+}
+
+impl<'i, 'p, R> TreeInfo for TextTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    type Node = (CstNode<'i, R>, usize);
+
+    fn root(&self) -> Self::Node {
+        (self.plot.filter.cst_node(self.cst.clone().next().unwrap()), 0)
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let depth = node.1 + 1;
+        let children = if depth > self.fold { vec![] } else { self.visible(&node.0) };
+        children.into_iter().map(move |node| (node, depth))
+    }
+
+    fn width(&self, node: &Self::Node) -> Coordinate {
+        (self.label(node).width() + 4) as Coordinate
+    }
+
+    fn height(&self, _: &Self::Node) -> Coordinate {
+        NODE_HEIGHT as Coordinate
+    }
+}
+
+impl<'i, 'p, R> TextTree<'i, 'p, R>
+where
+    R: YggdrasilRule,
+{
+    /// The children drawn below a node
+    fn visible(&self, node: &CstNode<'i, R>) -> Vec<CstNode<'i, R>> {
+        let filter = &self.plot.filter;
+        filter.visible_children(node).into_iter().map(|pair| filter.cst_node(pair)).collect()
+    }
+    /// The rule names of a node, the text of a leaf, and the number of hidden nodes when folded
+    fn label(&self, (node, depth): &(CstNode<'i, R>, usize)) -> String {
+        if !node.has_child(false) {
+            return self.plot.label_format.format(&node.get_string());
+        }
+        let rule = node.rules().join(" › ");
+        if *depth < self.fold {
+            return rule;
+        }
+        let mut hidden = 0;
+        let mut stack = self.visible(node);
+        while let Some(node) = stack.pop() {
+            hidden += 1;
+            stack.extend(self.visible(&node));
+        }
+        if hidden == 0 {
+            rule
+        }
+        else {
+            format!("{} +{}", rule, hidden)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+/// Run the binary with the input on stdin
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ygg-viewer"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the binary may fail before it reads the input
+    if let Err(e) = child.stdin.take().unwrap().write_all(input.as_bytes()) {
+        assert_eq!(e.kind(), ErrorKind::BrokenPipe, "{}", e);
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn output_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+#[test]
+fn test_rule_lookup() {
+    let output = run(&["--rule", "classstatement"], "class A { B }");
+    assert!(output.status.success());
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"data-rule="ClassStatement""#));
+    assert!(!svg.contains(r#"data-rule="Root""#));
+
+    // every rule of the grammar is a start rule
+    let output = run(&["--rule", "classblock"], "{ B }");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains(r#"data-rule="ClassBlock""#));
+
+    let output = run(&["--rule", "Nope"], "class A { B }");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: unknown rule `Nope`, expected one of Root, Statement, "), "{}", stderr);
+    assert!(stderr.ends_with(", WhiteSpace, Comment\n"), "{}", stderr);
+    // the labels of literals are not rules
+    assert!(run(&["--rule", "IgnoreText"], "x").stderr.starts_with(b"error: unknown rule `IgnoreText`"));
+}
+
+#[test]
+fn test_id_prefix() {
+    let svg = run(&[], "class A { B }").stdout;
+    assert!(String::from_utf8(svg).unwrap().contains(r#"id="cst-title""#));
+    let output = run(&["--id-prefix", "left-"], "class A { B }");
+    assert!(output.status.success());
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.contains(r#"id="left-title""#));
+    assert!(!svg.contains(r#"id="cst-"#));
+}
+
+#[test]
+fn test_format_guessing() {
+    for (name, magic) in [("tree.json", &b"{\"version\":1,"[..]), ("tree.dot", b"digraph cst {"), ("tree.png", b"\x89PNG")] {
+        let path = output_path(name);
+        let output = run(&["-o", path.to_str().unwrap()], "class A { B }");
+        assert!(output.status.success(), "{}: {}", name, String::from_utf8_lossy(&output.stderr));
+        assert!(std::fs::read(&path).unwrap().starts_with(magic), "{}", name);
+    }
+    // an explicit format wins over the extension
+    let path = output_path("tree.txt");
+    let output = run(&["-o", path.to_str().unwrap(), "--format", "dot"], "class A { B }");
+    assert!(output.status.success());
+    assert!(std::fs::read(&path).unwrap().starts_with(b"digraph cst {"));
+
+    let output = run(&["-o", path.to_str().unwrap()], "class A { B }");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("error: ") && stderr.ends_with("tree.txt: can not guess the format, use --format\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    let output = run(&["--rule", "ClassStatement"], "class A { ) }");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: <stdin>: failed to parse as ClassStatement\n"), "{}", stderr);
+    assert!(!stderr.contains("panicked"));
+
+    // the failure is still drawn, and the exit code still reports it
+    let output = run(&["--rule", "ClassStatement", "--show-failure"], "class A { ) }");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("parse-failure"));
+    let output = run(&["--rule", "ClassStatement", "--show-failure", "--format", "dot"], "class A { ) }");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("can not draw the failure as Dot, use svg or html"));
+}
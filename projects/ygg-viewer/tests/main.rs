@@ -1,6 +1,12 @@
+#![cfg(feature = "yggdrasil")]
+
+use tree_layout::TreeInfo;
 use yggdrasil_parser::{bootstrap::BootstrapRule, BootstrapParser};
 use yggdrasil_rt::YggdrasilParser;
-use yggdrasil_viewer::SvgPlotter;
+use yggdrasil_viewer::{
+    Focus, FontEmbedding, LabelFormat, LineIndex, NodeLabel, NodeShape, NodeSpan, NodeStyle, Overflow, ParseFailure,
+    SvgPlotter, TextMetrics, TextPlotter, Theme,
+};
 
 #[test]
 fn ready() {
@@ -21,8 +27,491 @@ class ClassStatement {
     svg::save("tests/bootstrap.svg", &tree).unwrap();
 }
 
+#[test]
+fn test_thumbnail() {
+    let plotter = SvgPlotter::default()
+        .with_margins(4.0, 2.0)
+        .with_layered(false)
+        .with_padding(2.0, 0.0)
+        .with_font_size(8.0)
+        .with_corner_radius(1.0)
+        .with_min_width(10.0)
+        .with_style("svg rect { fill: gray; }");
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let svg = plotter.draw(cst).to_string();
+    assert!(svg.contains(r#"font-size="8""#));
+    assert!(svg.contains(r#"rx="1""#));
+    assert!(svg.contains("fill: gray"));
+}
+
+#[test]
+fn test_theme() {
+    let theme = Theme::dark().with_rule(BootstrapRule::ClassStatement, NodeStyle::new("red", "none", "white")).with_mapper(
+        |rule, is_leaf| match rule {
+            "Root" if !is_leaf => Some(NodeStyle::new("blue", "none", "white").with_shape(NodeShape::Ellipse)),
+            _ => None,
+        },
+    );
+    assert_eq!(theme.style_of("ClassStatement", false).fill, "red");
+    assert_eq!(theme.style_of("KW_CLASS", true), Theme::dark().keyword.unwrap());
+    assert_eq!(theme.style_of("Unknown", true), Theme::dark().leaf);
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let svg = SvgPlotter::default().with_theme(theme).draw(cst).to_string();
+    assert!(svg.contains(r#"class="node ClassStatement""#));
+    assert!(svg.contains("svg rect.node.ClassStatement, svg ellipse.node.ClassStatement { fill: red; stroke: none; }"));
+    assert!(svg.contains("<ellipse"));
+}
+
+#[test]
+fn test_label_format() {
+    let format = LabelFormat::default();
+    assert_eq!(format.format(""), "ε");
+    assert_eq!(format.format("a b\tc\r\nd\n"), "a·b→c⏎d⏎");
+    assert_eq!(format.format("\u{1b}[0m\0"), "\\u{1b}[0m\\0");
+    assert_eq!(format.format("\u{a0}"), "\\u{a0}");
+    assert_eq!(format.with_max_chars(5).format("abcdefg"), "abcd…");
+    assert_eq!(format.with_max_chars(5).format("abcde"), "abcde");
+    assert_eq!(format.with_visible_whitespace(false).format("a b\n"), "a b\\n");
+
+    let cst = BootstrapParser::parse_cst("class A { \"x\u{1b}\" }", BootstrapRule::Root).unwrap();
+    let svg = SvgPlotter::default().draw(cst).to_string();
+    assert!(svg.contains("<title>\nclass\n</title>"));
+    assert!(svg.contains("<title>\nx\\u{1b}\n</title>"));
+
+    // runs of spaces are not collapsed when the whitespace is not made visible
+    let cst = BootstrapParser::parse_cst(r#"class A { "x   y" }"#, BootstrapRule::Root).unwrap();
+    let plotter = SvgPlotter::default().with_label_format(LabelFormat::default().with_visible_whitespace(false));
+    let svg = plotter.draw(cst).to_string();
+    assert!(svg.contains("x \u{a0}\u{a0}y"));
+}
+
+#[test]
+fn test_span_metadata() {
+    let text = "class A\n{ B }";
+    let cst = BootstrapParser::parse_cst(text, BootstrapRule::Root).unwrap();
+    let root = cst.clone().into_iter().next().unwrap();
+    let span = NodeSpan::of(&root, &LineIndex::new(text));
+    assert_eq!((span.start, span.end), (0, text.len()));
+    assert_eq!(span.range(), "1:1-2:6");
+    let svg = SvgPlotter::default().draw(cst).to_string();
+    assert!(svg.contains(r#"data-rule="Root""#));
+    assert!(svg.contains(r#"data-start="0""#));
+    assert!(svg.contains(r#"data-end="13""#));
+    assert!(svg.contains(r#"data-range="1:1-2:6""#));
+}
+
+#[test]
+fn test_line_index() {
+    // the same lines and columns as the parser finds for every offset
+    let text = "ä\r\nb\rc\n\n中e\u{301}\n";
+    let lines = LineIndex::new(text);
+    for offset in (0..=text.len()).filter(|&offset| text.is_char_boundary(offset)) {
+        let position = yggdrasil_rt::Position::new(text, offset).unwrap();
+        assert_eq!(lines.line_column(offset), position.line_column(), "{}", offset);
+    }
+}
+
+#[test]
+fn test_html() {
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let html = SvgPlotter::default().draw_html(cst);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<svg"));
+    assert!(html.contains("data-parent"));
+    assert!(html.contains("<script>"));
+    // nothing is loaded from the network, only the svg namespace is an url
+    let html = html.replace("http://www.w3.org/2000/svg", "");
+    assert!(!html.contains("http://") && !html.contains("https://"));
+}
+
+#[test]
+fn test_playground() {
+    let text = "class A\n{ \"B<C>\" }";
+    let cst = BootstrapParser::parse_cst(text, BootstrapRule::Root).unwrap();
+    let html = SvgPlotter::default().draw_playground(text, cst);
+    assert!(html.contains(r#"<pre id="source">"#));
+    assert!(html.contains(r#"<span class="ln">2</span>"#));
+    assert!(html.contains(r#"<span data-start="0" data-end="5">class</span>"#));
+    assert!(html.contains("B&lt;C&gt;"));
+    assert!(html.contains("window.cstViewer"));
+
+    // blank lines and the line after a trailing newline are empty
+    let text = "class A { B }\n\nclass C { D }\n";
+    let cst = BootstrapParser::parse_cst(text, BootstrapRule::Root).unwrap();
+    let html = SvgPlotter::default().draw_playground(text, cst);
+    assert!(html.contains(r#"<span class="ln">2</span><code></code>"#));
+    assert!(html.contains(r#"<span data-start="15" data-end="20">class</span>"#));
+    assert!(html.contains(r#"<span class="ln">4</span><code></code></div></pre>"#));
+}
+
+#[test]
+fn test_dot() {
+    let cst = BootstrapParser::parse_cst("class A { \"B\\\\\" }", BootstrapRule::Root).unwrap();
+    let dot = SvgPlotter::default().draw_dot(cst.clone(), false);
+    assert!(dot.starts_with("digraph cst {"));
+    assert!(dot.contains("ordering=out"));
+    assert!(dot.contains(r#"n0 [label="Root""#));
+    assert!(dot.contains(r#"label="\\\\""#));
+    assert!(dot.contains(r#"tooltip="\"B\\\\\"""#));
+    assert!(dot.contains("n0 -> n1;"));
+    assert!(!dot.contains("pos="));
+    let dot = SvgPlotter::default().draw_dot(cst, true);
+    assert!(dot.contains("pos=\""));
+    assert!(dot.contains("fixedsize=true"));
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn test_png() {
+    use resvg::tiny_skia::Pixmap;
+    use yggdrasil_viewer::PngOptions;
+    let plotter = SvgPlotter::default().with_theme(Theme::dark());
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let normal = Pixmap::decode_png(&plotter.draw_png(cst.clone(), &PngOptions::default()).unwrap()).unwrap();
+    let retina = Pixmap::decode_png(&plotter.draw_png(cst, &PngOptions::default().with_dpi(192.0)).unwrap()).unwrap();
+    assert!(normal.width() > 0 && normal.height() > 0);
+    assert!(retina.width().abs_diff(2 * normal.width()) <= 1);
+    // the dark background is opaque
+    assert_eq!(normal.pixel(0, 0).unwrap().alpha(), 255);
+}
+
+#[test]
+fn test_text_plot() {
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let text = TextPlotter::default().draw(cst.clone());
+    assert!(text.contains("│ ClassStatement │"));
+    assert!(text.contains("│ class │"));
+    assert!(!text.contains('\x1b'));
+    let colored = TextPlotter::default().with_colored(true).draw(cst.clone());
+    assert!(colored.contains("\x1b[48;2;"));
+
+    let folded = TextPlotter::default().with_max_width(30).with_overflow(Overflow::Fold).draw(cst.clone());
+    assert!(folded.contains("+"));
+    assert!(folded.lines().all(|line| line.chars().count() <= 30));
+    let scrolled = TextPlotter::default().with_max_width(30).draw(cst);
+    assert!(scrolled.lines().all(|line| line.chars().count() <= 30));
+    assert!(scrolled.lines().count() > text.lines().count());
+}
+
+#[test]
+fn test_text_filter() {
+    let cst = BootstrapParser::parse_cst("class A { \"e\u{301}中\u{200b}\" }", BootstrapRule::Root).unwrap();
+    let text = TextPlotter::default().draw(cst.clone());
+    assert!(text.contains("│ e\u{301}中\u{200b} │"));
+    // the same nodes as the svg plotter draws
+    let text = TextPlotter::default().with_compress_chains(true).with_hidden_rule(BootstrapRule::ClassBlock).draw(cst.clone());
+    assert!(text.contains("│ Root › Statement › ClassStatement │"));
+    assert!(!text.contains("ClassBlock"));
+    assert!(!text.contains("│ {"));
+    let trivia = TextPlotter::default().with_trivia(true).draw(cst);
+    assert!(trivia.lines().map(|line| line.matches('╭').count()).sum::<usize>() > text.matches('╭').count());
+}
+
+#[test]
+fn test_positioned() {
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let tree = SvgPlotter::default().draw_positioned(cst);
+    assert_eq!(tree.nodes[0].rule, "Root");
+    assert_eq!(tree.nodes[0].parent, None);
+    assert_eq!(tree.edges.len(), tree.nodes.len() - 1);
+    for (id, node) in tree.nodes.iter().enumerate() {
+        assert_eq!(node.id, id);
+        assert!(node.rect.x >= 0.0 && node.rect.x + node.rect.width <= tree.width + 1e-9);
+        for child in node.children.iter() {
+            assert_eq!(tree.nodes[*child].parent, Some(id));
+            assert_eq!(tree.nodes[*child].depth, node.depth + 1);
+        }
+    }
+    let leaf = tree.nodes.iter().find(|node| node.leaf).unwrap();
+    assert_eq!(leaf.text.as_deref(), Some("class"));
+    // the box of `class A` next to the focused block spans both tokens
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let focused = SvgPlotter::default().with_focus(Focus::bytes(10, 11).with_above(10)).draw_positioned(cst);
+    let stub = focused.nodes.iter().find(|node| node.label == "+2").unwrap();
+    assert_eq!((stub.span.start, stub.span.end), (0, 7));
+    #[cfg(feature = "json")]
+    {
+        let json = tree.to_json();
+        assert!(json.starts_with(r#"{"version":1,"#));
+        assert!(json.contains(r#""rule":"ClassStatement""#));
+    }
+}
+
+#[test]
+fn test_filter() {
+    let count = |svg: &str| svg.matches(r#"class="cst-node""#).count();
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let full = SvgPlotter::default().draw(cst.clone()).to_string();
+    // Root > Statement > ClassStatement is one node, so is ClassBlock > Expression > ... > Atomic
+    let compressed = SvgPlotter::default().with_compress_chains(true).draw(cst.clone()).to_string();
+    assert_eq!(count(&compressed), count(&full) - 8);
+    assert!(compressed.contains(r#"data-rule="Root &gt; Statement &gt; ClassStatement""#));
+    // the tokens move up to the root
+    let hidden = SvgPlotter::default()
+        .with_hidden_rule(BootstrapRule::Statement)
+        .with_hidden_rule_name("ClassStatement")
+        .draw(cst.clone())
+        .to_string();
+    assert_eq!(count(&hidden), count(&full) - 2);
+    assert!(!hidden.contains(r#"data-rule="Statement""#));
+    let trivia = SvgPlotter::default().with_trivia(true).draw(cst).to_string();
+    assert!(trivia.contains(r#"data-rule="WhiteSpace""#));
+    assert!(!full.contains(r#"data-rule="WhiteSpace""#));
+}
+
+#[test]
+fn test_diff() {
+    let old = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let new = BootstrapParser::parse_cst("class A { C D }", BootstrapRule::Root).unwrap();
+    let same = SvgPlotter::default().draw_diff(old.clone(), old.clone()).to_string();
+    assert!(same.contains("diff-same"));
+    assert!(!same.contains(r#"class="cst-node diff-added"#));
+    assert!(!same.contains(r#"class="cst-node diff-relabeled"#));
+    let diff = SvgPlotter::default().draw_diff(old.clone(), new.clone()).to_string();
+    assert!(diff.contains(r#"class="cst-node diff-added""#));
+    assert!(diff.contains(r#"class="cst-node diff-relabeled""#));
+    assert!(diff.contains("label-old"));
+    // removing the tokens again
+    let diff = SvgPlotter::default().draw_diff(new.clone(), old.clone()).to_string();
+    assert!(diff.contains(r#"class="cst-node diff-removed""#));
+    assert!(diff.contains(r#"data-side="old""#));
+    // the compressed rules and the labels are escaped
+    let diff = SvgPlotter::default().with_compress_chains(true).draw_diff(old.clone(), new.clone()).to_string();
+    assert!(diff.contains(r#"data-rule="Root &gt; Statement &gt; ClassStatement""#));
+    assert!(!diff.contains("Root > Statement"));
+    // the same tree of treeitems as a drawn tree
+    let diff = SvgPlotter::default().draw_diff(old.clone(), new.clone()).to_string();
+    assert!(diff.contains("<title id=\"cst-title\">\nChanges to the syntax tree of Root\n</title>"));
+    assert!(diff.contains("<desc id=\"cst-desc\">\n17 nodes, 4 added, 0 removed, 0 moved, 1 relabeled\n</desc>"));
+    assert!(diff.contains(r#"role="tree""#));
+    assert_eq!(diff.matches(r#"role="treeitem""#).count(), 17);
+    assert!(diff.contains(r#"aria-label="Identifier: A (same)" aria-level="4""#));
+    assert!(diff.contains(r#"aria-owns="cst-3 cst-4 cst-5""#));
+    let html = SvgPlotter::default().draw_diff_html(old, new);
+    assert!(html.contains("diff-old"));
+}
+
+#[test]
+fn test_focus() {
+    let text = "class A { B C D E }";
+    let cst = BootstrapParser::parse_cst(text, BootstrapRule::Root).unwrap();
+    let nodes = |svg: &str| svg.matches("class=\"cst-node\"").count();
+    let full = SvgPlotter::default().draw(cst.clone()).to_string();
+    let offset = text.find('C').unwrap();
+    let bytes = SvgPlotter::default().with_focus(Focus::bytes(offset, offset + 1)).draw(cst.clone()).to_string();
+    assert!(nodes(&bytes) < nodes(&full));
+    // the ancestors above the three drawn ones are one row of rule names
+    assert!(bytes.contains(r#"<text class="breadcrumb""#));
+    assert!(bytes.contains("Root › Statement › ClassStatement › ClassBlock › Expression › ExpressionHard › ExpressionSoft\n"));
+    assert!(!bytes.contains("ExpressionSoft › ExpressionTag"));
+    // the same range by line and column
+    let lines = SvgPlotter::default().with_focus(Focus::lines((1, offset + 1), (1, offset + 2))).draw(cst.clone()).to_string();
+    assert_eq!(lines, bytes);
+    // from ExpressionSoft down, the boxes of B and of D E span the siblings they stand for
+    let siblings =
+        SvgPlotter::default().with_focus(Focus::bytes(offset, offset + 1).with_above(4)).draw(cst.clone()).to_string();
+    assert_eq!(siblings.matches(r#"<rect class="stub""#).count(), 2);
+    assert!(siblings.contains("nodes not drawn"));
+    assert!(siblings.contains(r#"data-end="12" data-id="1" data-parent="0" data-range="1:11-1:13""#));
+    assert!(siblings.contains(r#"data-end="18" data-id="6" data-parent="0" data-range="1:15-1:19""#));
+    // only the focused node below the path to it
+    let tight = Focus::bytes(offset, offset + 1).with_above(0).with_below(0);
+    let svg = SvgPlotter::default().with_focus(tight).draw(cst.clone()).to_string();
+    assert_eq!(nodes(&svg), 1);
+    assert!(svg.contains("ExpressionTag › Term › Atomic\n"));
+    // no breadcrumb when every ancestor is drawn
+    let svg = SvgPlotter::default().with_focus(Focus::bytes(offset, offset + 1).with_above(10)).draw(cst).to_string();
+    assert!(!svg.contains("breadcrumb\""));
+}
+
+#[test]
+fn test_failure() {
+    let text = "{\n    B )\n}";
+    let error = BootstrapParser::parse_cst(text, BootstrapRule::ClassBlock).unwrap_err();
+    let failure = ParseFailure::from_error(text, &error);
+    assert_eq!(failure.offset(), text.find(')').unwrap());
+    assert_eq!(failure.line_col(), (2, 7));
+    assert!(failure.summary().starts_with("2:7: expected ExpressionTag"));
+    assert_eq!(failure.consumed().len() + failure.unconsumed().len(), text.len());
+    let svg = SvgPlotter::default().draw_failure(&failure).to_string();
+    assert!(svg.contains("parse-failure"));
+    assert!(svg.contains(r#"class="consumed""#));
+    assert!(svg.contains("data-failure-start"));
+    // a failure with the tree of the text before it
+    let partial = BootstrapParser::parse_cst("B", BootstrapRule::Identifier).unwrap();
+    let failure = ParseFailure::new(text, 6).with_expected("Identifier").with_partial(partial);
+    assert_eq!(failure.line_col(), (2, 5));
+    assert_eq!(failure.summary(), "2:5: expected Identifier");
+    let svg = SvgPlotter::default().draw_failure(&failure).to_string();
+    assert!(svg.contains("cst-node"));
+    assert!(svg.contains("failure-mark"));
+    let html = SvgPlotter::default().draw_failure_html(&failure);
+    assert!(html.contains(r#"<pre id="source">"#));
+}
+
+/// Nested lists, every node is the path of child indices from the root
+struct Lists(Vec<Vec<&'static str>>);
+
+impl TreeInfo for Lists {
+    type Node = Vec<usize>;
+
+    fn root(&self) -> Self::Node {
+        vec![]
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let count = match node.as_slice() {
+            [] => self.0.len(),
+            [list] => self.0[*list].len(),
+            _ => 0,
+        };
+        let node = node.clone();
+        (0..count).map(move |i| [node.as_slice(), &[i]].concat())
+    }
+}
+
+impl NodeLabel for Lists {
+    fn label(&self, node: &Self::Node) -> String {
+        match node.as_slice() {
+            [] => "lists".to_string(),
+            [list] => format!("list {}", list),
+            [list, item, ..] => self.0[*list][*item].to_string(),
+        }
+    }
+    fn rule(&self, node: &Self::Node) -> Option<String> {
+        Some(if node.len() == 2 { "Item" } else { "List" }.to_string())
+    }
+}
+
+#[test]
+fn test_generic_tree() {
+    let theme = Theme::default().with_rule_name("Item", NodeStyle::new("gold", "none", "black"));
+    let svg = SvgPlotter::default().with_theme(theme).draw_tree(Lists(vec![vec!["a", "b"], vec!["c"]])).to_string();
+    assert_eq!(svg.matches("class=\"cst-node\"").count(), 6);
+    assert!(svg.contains(r#"class="leaf Item""#));
+    assert!(svg.contains("fill: gold"));
+    assert!(svg.contains(r#"data-rule="List""#));
+}
+
+/// A root with one leaf per rule, the leaves are styled by their rule name
+struct Rules(Vec<&'static str>);
+
+impl TreeInfo for Rules {
+    type Node = Option<usize>;
+
+    fn root(&self) -> Self::Node {
+        None
+    }
+
+    fn children(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node> {
+        let count = if node.is_none() { self.0.len() } else { 0 };
+        (0..count).map(Some)
+    }
+}
+
+impl NodeLabel for Rules {
+    fn label(&self, node: &Self::Node) -> String {
+        node.map_or("rules".to_string(), |i| i.to_string())
+    }
+    fn rule(&self, node: &Self::Node) -> Option<String> {
+        Some(node.map_or("Root", |i| self.0[i]).to_string())
+    }
+}
+
+#[test]
+fn test_theme_classes() {
+    let theme = Theme::default()
+        .with_rule_name("a-b", NodeStyle::new("gold", "none", "black"))
+        .with_rule_name("a b", NodeStyle::new("red; } svg { display: none", "none", "black"));
+    let svg = SvgPlotter::default().with_theme(theme).draw_tree(Rules(vec!["a-b", "a_b", "a b", "1<x"])).to_string();
+    // every rule has its own class
+    assert!(svg.contains(r#"class="leaf a-b""#));
+    assert!(svg.contains(r#"class="leaf a_b""#));
+    assert!(svg.contains(r#"class="leaf a%20b""#));
+    assert!(svg.contains(r#"class="leaf 1&lt;x""#));
+    assert!(svg.contains("svg rect.leaf.a-b, svg ellipse.leaf.a-b { fill: gold; stroke: none; }"));
+    assert!(svg.contains("svg rect.leaf.a_b, svg ellipse.leaf.a_b { fill: #77c63d;"));
+    assert!(svg.contains("svg rect.leaf.a\\25 20b, svg ellipse.leaf.a\\25 20b { fill: initial; stroke: none; }"));
+    assert!(svg.contains("svg rect.leaf.\\31 \\3c x,"));
+    assert!(!svg.contains("display: none"));
+}
+
+#[test]
+fn test_text_metrics() {
+    let metrics = TextMetrics::default();
+    // proportional advances, not byte counts
+    assert!(metrics.line_width("iii") < metrics.line_width("WWW"));
+    // a wide character takes a full em, a combining mark takes nothing
+    assert_eq!(metrics.line_width("中文"), 2.0 * metrics.font_size());
+    assert_eq!(metrics.line_width("e\u{301}"), metrics.line_width("e"));
+    let (width, height) = metrics.text_size("a\nbbb");
+    assert_eq!(width, metrics.line_width("bbb"));
+    assert_eq!(height, 2.0 * metrics.line_height());
+}
+
 // fn main() {
 //     let root = layered();
 //     let layout = layout_position(&Tree, &root);
 //     println!("{:?}", layout)
 // }
+
+#[test]
+fn test_accessible() {
+    let cst = BootstrapParser::parse_cst("class A { B }", BootstrapRule::Root).unwrap();
+    let svg = SvgPlotter::default().draw(cst.clone()).to_string();
+    assert!(svg.contains(r#"aria-labelledby="cst-title""#));
+    assert!(svg.contains("<title id=\"cst-title\">\nSyntax tree of Root: class A { B }\n</title>"));
+    assert!(svg.contains("<desc id=\"cst-desc\">\n13 nodes in 11 levels, 3 of them leaves\n</desc>"));
+    assert!(svg.contains(r#"role="tree""#));
+    assert_eq!(svg.matches(r#"role="treeitem""#).count(), 13);
+    assert!(svg.contains(r#"aria-label="Identifier: A" aria-level="4""#));
+    assert!(svg.contains(r#"aria-owns="cst-3 cst-4 cst-5""#));
+    // several trees in one page
+    let svg = SvgPlotter::default().with_id_prefix("left-").draw(cst).to_string();
+    assert!(svg.contains(r#"id="left-0""#));
+    assert!(svg.contains(r#"aria-owns="left-1""#));
+}
+
+#[test]
+fn test_font_embedding() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    let cst = BootstrapParser::parse_cst("class A { \"Ä\" }", BootstrapRule::Root).unwrap();
+    let system = SvgPlotter::default().draw(cst.clone()).to_string();
+    assert!(!system.contains("@font-face"));
+
+    let embedded = |svg: &str| {
+        let start = svg.find("base64,").unwrap() + "base64,".len();
+        let end = start + svg[start..].find('"').unwrap();
+        STANDARD.decode(&svg[start..end]).unwrap()
+    };
+    let svg = SvgPlotter::default().with_font_embedding(FontEmbedding::Subset).draw(cst.clone()).to_string();
+    let font = embedded(&svg);
+    let face = ttf_parser::Face::parse(&font, 0).unwrap();
+    // the composite glyph keeps its components
+    let glyph = face.glyph_index('Ä').unwrap();
+    assert!(
+        face.glyph_bounding_box(glyph).unwrap().y_max > face.glyph_bounding_box(face.glyph_index('A').unwrap()).unwrap().y_max
+    );
+    assert!(face.glyph_index('Z').is_none());
+    // only the characters of the labels, not those of the markup or the titles
+    for c in ['=', '<', '"', '{', 'w'] {
+        assert!(face.glyph_index(c).is_none(), "{}", c);
+    }
+    assert!(font.len() < 64 * 1024);
+    assert_eq!(svg.matches("<text").count(), system.matches("<text").count());
+
+    // the summary and the source lines of a failure
+    let failure = ParseFailure::<BootstrapRule>::new("{\n    B )\n}", 6).with_expected("Identifier");
+    let svg = SvgPlotter::default().with_font_embedding(FontEmbedding::Subset).draw_failure(&failure).to_string();
+    let font = embedded(&svg);
+    let face = ttf_parser::Face::parse(&font, 0).unwrap();
+    for c in ['2', ':', 'x', 'B', ')', '}'] {
+        assert!(face.glyph_index(c).is_some(), "{}", c);
+    }
+    assert!(face.glyph_index('=').is_none());
+
+    let svg = SvgPlotter::default().with_font_embedding(FontEmbedding::Outlines).draw(cst).to_string();
+    assert!(!svg.contains("<text"));
+    assert!(svg.contains(r#"<path class="node Root" d="M"#));
+    assert!(svg.contains("svg text.node.Root, svg path.node.Root"));
+}